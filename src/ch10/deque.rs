@@ -0,0 +1,204 @@
+use std::marker::PhantomData;
+use std::ptr;
+use std::sync::atomic::{fence, AtomicIsize, AtomicPtr};
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release, SeqCst};
+
+// Chase-Lev work-stealing deque (고정 크기).
+// 주인 스레드 하나가 bottom 쪽에서 push/pop 하고, 다른 스레드들은 top 쪽에서 steal 한다.
+// 원소가 하나 남았을 때만 주인과 도둑이 경쟁하므로, 그때는 주인도 top 을 CAS 한다.
+//
+// CAS 에 진 도둑은 주인이 그 칸을 다시 쓰는 동안 읽었을 수 있다. 칸이 일반 메모리라면 그 자체로 data race 이므로,
+// 값은 Box 에 넣고 칸에는 그 포인터만 atomic 으로 둔다. 도둑은 CAS 에 이긴 뒤에만 포인터를 따라간다.
+pub struct WorkStealingDeque<T> {
+    buffer: Box<[AtomicPtr<T>]>,
+    top: AtomicIsize,
+    bottom: AtomicIsize,
+}
+
+unsafe impl<T: Send> Sync for WorkStealingDeque<T> {}
+
+pub struct Worker<'a, T> {
+    deque: &'a WorkStealingDeque<T>,
+    _no_sync: PhantomData<*const ()>,
+}
+
+unsafe impl<T: Send> Send for Worker<'_, T> {}
+
+pub struct Stealer<'a, T> {
+    deque: &'a WorkStealingDeque<T>,
+}
+
+// derive 하면 T: Clone 을 요구하게 되므로 직접 구현한다.
+impl<T> Clone for Stealer<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Stealer<'_, T> {}
+
+impl<T> WorkStealingDeque<T> {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be non-zero");
+        Self {
+            buffer: (0..capacity).map(|_| AtomicPtr::new(ptr::null_mut())).collect(),
+            top: AtomicIsize::new(0),
+            bottom: AtomicIsize::new(0),
+        }
+    }
+
+    // Worker 는 하나뿐이어야 하므로 &mut self 로 한 번에 하나만 만든다.
+    pub fn split(&mut self) -> (Worker<'_, T>, Stealer<'_, T>) {
+        (Worker { deque: self, _no_sync: PhantomData }, Stealer { deque: self })
+    }
+
+    fn slot(&self, i: isize) -> &AtomicPtr<T> {
+        &self.buffer[i as usize % self.buffer.len()]
+    }
+}
+
+impl<T> Worker<'_, T> {
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        let d = self.deque;
+        let b = d.bottom.load(Relaxed);
+        // Acquire 로 steal 이 칸을 비운 것을 본 뒤에만 그 칸을 다시 쓴다.
+        let t = d.top.load(Acquire);
+        if b - t >= d.buffer.len() as isize {
+            return Err(value);
+        }
+        // 포인터와 Box 의 내용은 아래의 bottom store(Release) 가 도둑에게 보이게 한다.
+        d.slot(b).store(Box::into_raw(Box::new(value)), Relaxed);
+        d.bottom.store(b + 1, Release);
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let d = self.deque;
+        let b = d.bottom.load(Relaxed) - 1;
+        d.bottom.store(b, Relaxed);
+        // bottom 을 줄인 것이 top 을 읽는 것보다 먼저 모든 도둑에게 보여야 한다.
+        fence(SeqCst);
+        let t = d.top.load(Relaxed);
+
+        if t > b {
+            // 비어 있다.
+            d.bottom.store(b + 1, Relaxed);
+            return None;
+        }
+        if t < b {
+            // 두 개 이상 남아 있으므로 도둑은 b 에 닿을 수 없다.
+            return Some(unsafe { take(d.slot(b).load(Relaxed)) });
+        }
+        // 마지막 하나: 도둑과 같은 CAS 로 경쟁한다.
+        let won = d.top.compare_exchange(t, t + 1, SeqCst, Relaxed).is_ok();
+        d.bottom.store(b + 1, Relaxed);
+        won.then(|| unsafe { take(d.slot(b).load(Relaxed)) })
+    }
+}
+
+impl<T> Stealer<'_, T> {
+    // 비어 있거나 다른 스레드와의 경쟁에서 지면 None 을 돌려준다.
+    pub fn steal(&self) -> Option<T> {
+        let d = self.deque;
+        let t = d.top.load(Acquire);
+        fence(SeqCst);
+        let b = d.bottom.load(Acquire);
+        if t >= b {
+            return None;
+        }
+        // CAS 에 지면 그 사이에 주인이 이 칸을 다시 썼을 수 있으므로, 포인터만 먼저 읽어 두고
+        // CAS 에 성공했을 때만 따라간다. 이기면 t 번 원소는 이 도둑의 것이고, 주인은 top 이 t 를 지나기 전에는 칸을 다시 쓰지 않는다.
+        let value = d.slot(t).load(Relaxed);
+        if d.top.compare_exchange(t, t + 1, SeqCst, Relaxed).is_err() {
+            return None;
+        }
+        Some(unsafe { take(value) })
+    }
+}
+
+// push 가 Box::into_raw 로 칸에 넣은 값을 꺼낸다. 원소 하나당 한 번만 호출해야 한다.
+unsafe fn take<T>(value: *mut T) -> T {
+    *Box::from_raw(value)
+}
+
+impl<T> Drop for WorkStealingDeque<T> {
+    fn drop(&mut self) {
+        for i in *self.top.get_mut()..*self.bottom.get_mut() {
+            drop(unsafe { take(*self.buffer[i as usize % self.buffer.len()].get_mut()) });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering::{Acquire, Release};
+    use std::thread;
+    use crate::ch10::deque::WorkStealingDeque;
+
+    #[test]
+    fn owner_is_lifo_stealer_is_fifo() {
+        let mut deque = WorkStealingDeque::new(4);
+        let (mut worker, stealer) = deque.split();
+        for i in 0..4 {
+            assert_eq!(worker.push(i), Ok(()));
+        }
+        assert_eq!(worker.push(4), Err(4));
+        assert_eq!(stealer.steal(), Some(0));
+        assert_eq!(worker.pop(), Some(3));
+        assert_eq!(worker.pop(), Some(2));
+        assert_eq!(worker.pop(), Some(1));
+        assert_eq!(worker.pop(), None);
+        assert_eq!(stealer.steal(), None);
+    }
+
+    // `cargo +nightly miri test ch10::deque` 로 돌리면 CAS 에 진 도둑이 data race 를 일으키지 않는지 Miri 가 확인한다.
+    #[test]
+    fn nothing_lost_or_duplicated() {
+        let count = if cfg!(miri) { 300 } else { 10_000 };
+        let mut deque = WorkStealingDeque::new(64);
+        let (mut worker, stealer) = deque.split();
+        let done = AtomicBool::new(false);
+
+        let mut taken: Vec<usize> = thread::scope(|s| {
+            let stealers: Vec<_> = (0..3)
+                .map(|_| {
+                    s.spawn(|| {
+                        let mut got = Vec::new();
+                        loop {
+                            match stealer.steal() {
+                                Some(i) => got.push(i),
+                                None if done.load(Acquire) => break,
+                                None => thread::yield_now(),
+                            }
+                        }
+                        got
+                    })
+                })
+                .collect();
+
+            let mut got = Vec::new();
+            for mut i in 0..count {
+                while let Err(back) = worker.push(i) {
+                    i = back;
+                    got.extend(worker.pop());
+                }
+                if i % 3 == 0 {
+                    got.extend(worker.pop());
+                }
+            }
+            while let Some(i) = worker.pop() {
+                got.push(i);
+            }
+            done.store(true, Release);
+
+            for stealer in stealers {
+                got.extend(stealer.join().unwrap());
+            }
+            got
+        });
+
+        taken.sort_unstable();
+        assert_eq!(taken, (0..count).collect::<Vec<_>>());
+    }
+}
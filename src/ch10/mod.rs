@@ -0,0 +1,4 @@
+pub mod deque;
+pub mod pool;
+pub mod scatter_gather;
+pub mod stack;
\ No newline at end of file
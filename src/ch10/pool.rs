@@ -0,0 +1,90 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use crate::ch5::channel::blocking_queue::Queue;
+
+type Job = Box<dyn FnOnce() + Send>;
+
+// 고정된 수의 worker 스레드가 하나의 MPMC 큐에서 작업을 꺼내 실행한다.
+// None 은 종료 신호이고, Drop 이 worker 마다 하나씩 보낸다.
+// 작업이 panic 해도 worker 는 죽지 않고 다음 작업을 꺼낸다. 그러지 않으면 worker 가 하나씩 줄어들다가
+// 모두 사라진 뒤 execute 가 가득 찬 큐 앞에서 영원히 기다리게 된다.
+pub struct ThreadPool {
+    jobs: Arc<Queue<Option<Job>>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl ThreadPool {
+    pub fn new(threads: usize) -> Self {
+        assert!(threads > 0, "thread pool needs at least one worker");
+        // 큐가 가득 차면 execute 가 기다리므로, 작업을 너무 많이 쌓아 두지 않는다.
+        let jobs: Arc<Queue<Option<Job>>> = Arc::new(Queue::new(threads * 4));
+        let workers = (0..threads)
+            .map(|_| {
+                let jobs = jobs.clone();
+                thread::spawn(move || {
+                    while let Some(job) = jobs.pop() {
+                        // panic 은 기본 hook 이 이미 출력했으므로 결과는 버린다.
+                        let _ = panic::catch_unwind(AssertUnwindSafe(job));
+                    }
+                })
+            })
+            .collect();
+        Self { jobs, workers }
+    }
+
+    pub fn execute(&self, f: impl FnOnce() + Send + 'static) {
+        self.jobs.push(Some(Box::new(f)));
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        // 종료 신호는 이미 들어간 작업 뒤에 쌓이므로, 남은 작업을 모두 실행한 뒤에 worker 가 끝난다.
+        for _ in &self.workers {
+            self.jobs.push(None);
+        }
+        // 작업의 panic 은 worker 안에서 잡으므로 join 이 실패할 일은 없지만,
+        // drop 중에 panic 하면 abort 될 수 있으니 결과를 unwrap 하지 않는다.
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::Relaxed;
+    use crate::ch10::pool::ThreadPool;
+
+    #[test]
+    fn runs_all_jobs_before_drop_returns() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let pool = ThreadPool::new(4);
+        for _ in 0..100 {
+            let count = count.clone();
+            pool.execute(move || {
+                count.fetch_add(1, Relaxed);
+            });
+        }
+        drop(pool);
+        assert_eq!(count.load(Relaxed), 100);
+    }
+
+    #[test]
+    fn panicking_job_keeps_worker_alive() {
+        let count = Arc::new(AtomicUsize::new(0));
+        let pool = ThreadPool::new(1);
+        pool.execute(|| panic!("job failed"));
+        for _ in 0..10 {
+            let count = count.clone();
+            pool.execute(move || {
+                count.fetch_add(1, Relaxed);
+            });
+        }
+        drop(pool);
+        assert_eq!(count.load(Relaxed), 10);
+    }
+}
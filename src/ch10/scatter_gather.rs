@@ -0,0 +1,81 @@
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+use crate::ch5::channel::blocking_queue::Queue;
+
+// inputs 를 workers 개의 스레드에 나눠서 f 를 적용하고, 결과를 입력 순서대로 돌려준다.
+// 입력과 결과는 각각 MPMC 큐로 오가며, 순서는 함께 보내는 index 로 되찾는다.
+// 두 큐 모두 크기가 정해져 있으므로, 입력은 별도 스레드가 넣고 현재 스레드는 결과를 꺼내기만 한다.
+// (한 스레드가 둘 다 하면 입력 큐와 결과 큐가 모두 가득 찼을 때 서로 기다리게 된다.)
+// f 가 panic 하면 worker 가 그것을 잡아 결과 대신 보낸다. worker 가 그냥 죽으면 그 결과가 오지 않아
+// 현재 스레드가 영원히 기다리므로, 모든 결과를 받은 뒤 첫 panic 을 현재 스레드에서 다시 일으킨다.
+pub fn scatter_gather<I, O>(inputs: Vec<I>, workers: usize, f: impl Fn(I) -> O + Sync) -> Vec<O>
+where
+    I: Send,
+    O: Send,
+{
+    assert!(workers > 0, "scatter_gather needs at least one worker");
+    let n = inputs.len();
+    // None 은 worker 에게 보내는 종료 신호
+    let jobs: Queue<Option<(usize, I)>> = Queue::new(workers * 2);
+    let results: Queue<(usize, thread::Result<O>)> = Queue::new(workers * 2);
+
+    let mut outputs: Vec<Option<O>> = (0..n).map(|_| None).collect();
+    let mut panicked = None;
+    thread::scope(|s| {
+        for _ in 0..workers {
+            s.spawn(|| {
+                while let Some((i, input)) = jobs.pop() {
+                    results.push((i, panic::catch_unwind(AssertUnwindSafe(|| f(input)))));
+                }
+            });
+        }
+        s.spawn(|| {
+            for job in inputs.into_iter().enumerate() {
+                jobs.push(Some(job));
+            }
+            for _ in 0..workers {
+                jobs.push(None);
+            }
+        });
+
+        for _ in 0..n {
+            match results.pop() {
+                (i, Ok(output)) => outputs[i] = Some(output),
+                (_, Err(payload)) => {
+                    panicked.get_or_insert(payload);
+                }
+            }
+        }
+    });
+    if let Some(payload) = panicked {
+        panic::resume_unwind(payload);
+    }
+    outputs.into_iter().map(|o| o.expect("every input produces one output")).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ch10::scatter_gather::scatter_gather;
+
+    #[test]
+    fn squares_in_order() {
+        let squares = scatter_gather((0..100).collect(), 4, |x: u64| x * x);
+        assert_eq!(squares, (0..100).map(|x| x * x).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn more_workers_than_inputs() {
+        let lengths = scatter_gather(vec!["a", "bb"], 8, str::len);
+        assert_eq!(lengths, [1, 2]);
+        assert!(scatter_gather(Vec::<u32>::new(), 2, |x| x).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "bad input 7")]
+    fn panic_in_f_propagates() {
+        scatter_gather((0..100).collect(), 4, |x: u32| {
+            assert_ne!(x, 7, "bad input {x}");
+            x
+        });
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::HashSet;
+use std::mem::ManuallyDrop;
+use std::ptr;
+use std::sync::Mutex;
+use std::sync::atomic::{fence, AtomicBool, AtomicPtr};
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release, SeqCst};
+
+// Treiber stack: head 포인터 하나를 CAS 로 바꿔서 push/pop 한다.
+//
+// pop 은 head 를 읽고 (*head).next 를 읽은 뒤 CAS 하는데, 그 사이에 다른 스레드가 같은 노드를 pop 해서
+// 해제하면 해제된 메모리를 읽게 된다. 또 해제된 주소가 새 노드로 다시 쓰이면, CAS 는 head 가
+// "같은 주소" 라서 성공하지만 next 는 이미 낡은 값이다(ABA 문제).
+//
+// hazard pointer 로 이를 막는다. pop 하는 스레드는 읽으려는 노드를 자기 hazard 칸에 먼저 적어 두고,
+// pop 된 노드는 retired 목록에 모았다가 어떤 hazard 칸에도 적혀 있지 않을 때만 해제한다.
+// 누군가 보고 있는 노드는 해제되지 않으므로 그 주소가 다시 쓰이지도 않는다.
+pub struct TreiberStack<T> {
+    head: AtomicPtr<Node<T>>,
+    hazards: AtomicPtr<HazardSlot>,
+    retired: Mutex<Vec<*mut Node<T>>>,
+}
+
+struct Node<T> {
+    value: ManuallyDrop<T>,
+    // push 할 때 한 번만 쓰고, head 에 연결된 뒤에는 읽기만 한다.
+    next: *mut Node<T>,
+}
+
+// pop 하는 동안 한 스레드가 빌려 쓰는 hazard 칸. 한 번 만들면 스택이 drop 될 때까지 목록에 남는다.
+struct HazardSlot {
+    pointer: AtomicPtr<()>,
+    in_use: AtomicBool,
+    next: *mut HazardSlot,
+}
+
+// retired 가 이만큼 쌓이면 hazard 를 훑어서 해제할 수 있는 노드를 해제한다.
+const SCAN_THRESHOLD: usize = 64;
+
+unsafe impl<T: Send> Send for TreiberStack<T> {}
+unsafe impl<T: Send> Sync for TreiberStack<T> {}
+
+impl<T> TreiberStack<T> {
+    pub const fn new() -> Self {
+        Self {
+            head: AtomicPtr::new(ptr::null_mut()),
+            hazards: AtomicPtr::new(ptr::null_mut()),
+            retired: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn push(&self, value: T) {
+        let node = Box::into_raw(Box::new(Node {
+            value: ManuallyDrop::new(value),
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.head.load(Relaxed);
+        loop {
+            unsafe { (*node).next = head };
+            // Release: pop 하는 스레드가 노드의 내용을 볼 수 있게 한다.
+            match self.head.compare_exchange_weak(head, node, Release, Relaxed) {
+                Ok(_) => return,
+                Err(h) => head = h,
+            }
+        }
+    }
+
+    pub fn pop(&self) -> Option<T> {
+        let hazard = self.acquire_hazard();
+        let head = loop {
+            let head = self.head.load(Acquire);
+            if head.is_null() {
+                hazard.in_use.store(false, Release);
+                return None;
+            }
+            // head 를 hazard 에 적은 뒤에도 여전히 head 인지 확인해야 한다.
+            // 적기 전에 다른 스레드가 pop 해서 retire 했다면, 그 스레드의 scan 은 이 hazard 를 못 봤을 수 있다.
+            hazard.pointer.store(head.cast(), SeqCst);
+            if self.head.load(SeqCst) != head {
+                continue;
+            }
+            // 이제 head 는 hazard 가 지키므로 해제되지 않는다.
+            let next = unsafe { (*head).next };
+            if self.head.compare_exchange(head, next, Acquire, Relaxed).is_ok() {
+                break head;
+            }
+        };
+        hazard.pointer.store(ptr::null_mut(), Release);
+        hazard.in_use.store(false, Release);
+
+        // CAS 에 성공한 스레드만 값을 꺼낸다.
+        let value = unsafe { ManuallyDrop::take(&mut (*head).value) };
+        self.retire(head);
+        Some(value)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.load(Relaxed).is_null()
+    }
+
+    // 쓰지 않는 hazard 칸을 빌리고, 없으면 새로 만들어 목록 앞에 붙인다.
+    fn acquire_hazard(&self) -> &HazardSlot {
+        let mut slot = self.hazards.load(Acquire);
+        while !slot.is_null() {
+            let s = unsafe { &*slot };
+            if !s.in_use.load(Relaxed) && s.in_use.compare_exchange(false, true, Acquire, Relaxed).is_ok() {
+                return s;
+            }
+            slot = s.next;
+        }
+
+        let slot = Box::into_raw(Box::new(HazardSlot {
+            pointer: AtomicPtr::new(ptr::null_mut()),
+            in_use: AtomicBool::new(true),
+            next: ptr::null_mut(),
+        }));
+        let mut head = self.hazards.load(Relaxed);
+        loop {
+            unsafe { (*slot).next = head };
+            match self.hazards.compare_exchange_weak(head, slot, Release, Relaxed) {
+                Ok(_) => return unsafe { &*slot },
+                Err(h) => head = h,
+            }
+        }
+    }
+
+    fn retire(&self, node: *mut Node<T>) {
+        let mut retired = self.retired.lock().unwrap();
+        retired.push(node);
+        if retired.len() >= SCAN_THRESHOLD {
+            self.scan(&mut retired);
+        }
+    }
+
+    // 어떤 hazard 에도 적혀 있지 않은 retired 노드를 해제한다.
+    fn scan(&self, retired: &mut Vec<*mut Node<T>>) {
+        // pop 의 hazard store 와 짝을 이룬다.
+        // 그 store 를 여기서 못 봤다면, pop 쪽은 head 를 다시 읽을 때 노드가 이미 빠진 것을 본다.
+        fence(SeqCst);
+        let mut protected = HashSet::new();
+        let mut slot = self.hazards.load(Acquire);
+        while !slot.is_null() {
+            let s = unsafe { &*slot };
+            protected.insert(s.pointer.load(SeqCst) as usize);
+            slot = s.next;
+        }
+        retired.retain(|&node| {
+            if protected.contains(&(node as usize)) {
+                return true;
+            }
+            // 값은 pop 이 이미 꺼내 갔으므로 노드만 해제한다.
+            drop(unsafe { Box::from_raw(node) });
+            false
+        });
+    }
+}
+
+impl<T> Default for TreiberStack<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for TreiberStack<T> {
+    fn drop(&mut self) {
+        let mut node = *self.head.get_mut();
+        while !node.is_null() {
+            let mut boxed = unsafe { Box::from_raw(node) };
+            node = boxed.next;
+            unsafe { ManuallyDrop::drop(&mut boxed.value) };
+        }
+        for &node in self.retired.get_mut().unwrap().iter() {
+            drop(unsafe { Box::from_raw(node) });
+        }
+        let mut slot = *self.hazards.get_mut();
+        while !slot.is_null() {
+            let boxed = unsafe { Box::from_raw(slot) };
+            slot = boxed.next;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use crate::ch10::stack::TreiberStack;
+    use crate::ch5::drop_counter::DropCounter;
+
+    #[test]
+    fn lifo_order() {
+        let stack = TreiberStack::new();
+        for i in 0..5 {
+            stack.push(i);
+        }
+        for i in (0..5).rev() {
+            assert_eq!(stack.pop(), Some(i));
+        }
+        assert_eq!(stack.pop(), None);
+        assert!(stack.is_empty());
+    }
+
+    #[test]
+    fn concurrent_push_pop() {
+        const PER_THREAD: usize = 2500;
+        let stack = TreiberStack::new();
+        let popped = AtomicUsize::new(0);
+        let sum = AtomicUsize::new(0);
+
+        thread::scope(|s| {
+            for t in 0..4 {
+                let stack = &stack;
+                s.spawn(move || {
+                    for i in 0..PER_THREAD {
+                        stack.push(t * PER_THREAD + i);
+                    }
+                });
+            }
+            for _ in 0..4 {
+                s.spawn(|| {
+                    while popped.load(Relaxed) < 4 * PER_THREAD {
+                        match stack.pop() {
+                            Some(i) => {
+                                sum.fetch_add(i, Relaxed);
+                                popped.fetch_add(1, Relaxed);
+                            }
+                            None => thread::yield_now(),
+                        }
+                    }
+                });
+            }
+        });
+
+        let n = 4 * PER_THREAD;
+        assert_eq!(popped.load(Relaxed), n);
+        assert_eq!(sum.load(Relaxed), n * (n - 1) / 2);
+        assert!(stack.is_empty());
+    }
+
+    // 노드를 재활용하면서 push/pop 을 섞어서 계속 돌린다.
+    // `cargo +nightly miri test ch10::stack` 로 돌리면 해제된 노드를 읽는지 Miri 가 확인한다.
+    #[test]
+    fn reclaim_stress() {
+        let rounds = if cfg!(miri) { 200 } else { 20_000 };
+        let stack = TreiberStack::new();
+        let popped = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for i in 0..rounds {
+                        stack.push(i);
+                        if stack.pop().is_some() {
+                            popped.fetch_add(1, Relaxed);
+                        }
+                    }
+                });
+            }
+        });
+
+        let mut remaining = 0;
+        while stack.pop().is_some() {
+            remaining += 1;
+        }
+        assert_eq!(popped.load(Relaxed) + remaining, 4 * rounds);
+    }
+
+    #[test]
+    fn drops_remaining_values_once() {
+        let (a, drops_a) = DropCounter::new();
+        let (b, drops_b) = DropCounter::new();
+        let stack = TreiberStack::new();
+        stack.push(a);
+        stack.push(b);
+        drop(stack.pop());
+        assert_eq!(drops_b.load(Relaxed), 1);
+        drop(stack);
+        assert_eq!(drops_a.load(Relaxed), 1);
+        assert_eq!(drops_b.load(Relaxed), 1);
+    }
+}
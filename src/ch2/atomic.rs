@@ -3,18 +3,47 @@
 // fetch-and-modify
 // compare-and-exchange
 
-use std::sync::atomic::{AtomicBool, AtomicI32, AtomicUsize};
-use std::sync::atomic::Ordering::Relaxed;
+use std::sync::{Condvar, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicU64, AtomicU8, AtomicUsize};
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use crate::ch2::shutdown::ShutdownToken;
+use crate::ch2::stats::ConcurrentStats;
 
 // 정지 플래그
+// 백그라운드 스레드는 spin 하는 대신 park 하고, trigger 가 깨워 준다.
 pub fn example1() {
-    static STOP: AtomicBool = AtomicBool::new(false);
+    let stop = ShutdownToken::new();
+
+    thread::scope(|s| {
+        s.spawn(|| {
+            stop.register(thread::current());
+            while !stop.is_triggered() {
+                thread::park();
+            }
+        });
 
-    let background_thread = thread::spawn(|| {
-        while !STOP.load(Relaxed) {}
+        for line in std::io::stdin().lines() {
+            match line.unwrap().as_str() {
+                "help" => println!("commands: help, stop"),
+                "stop" => break,
+                cmd => println!("unknown command: {cmd:?}")
+            }
+        }
+
+        stop.trigger();
     });
+}
+
+// 정지 플래그 + 정지 확인
+// 백그라운드 스레드는 루프를 빠져나오기 직전에 STOPPED 를 Release 로 설정하고,
+// 메인 스레드는 Acquire 로 그것을 확인하므로 백그라운드 스레드가 마지막으로 한 작업이 보인다.
+pub fn example1_ack() {
+    static STOP: AtomicBool = AtomicBool::new(false);
+    static STOPPED: AtomicBool = AtomicBool::new(false);
+
+    let background_thread = thread::spawn(|| run_until_stopped(&STOP, &STOPPED));
 
     for line in std::io::stdin().lines() {
         match line.unwrap().as_str() {
@@ -25,9 +54,24 @@ pub fn example1() {
     }
 
     STOP.store(true, Relaxed);
+    wait_for_stopped(&STOPPED);
+    println!("background thread stopped");
     background_thread.join().unwrap();
 }
 
+fn run_until_stopped(stop: &AtomicBool, stopped: &AtomicBool) {
+    while !stop.load(Relaxed) {
+        std::hint::spin_loop();
+    }
+    stopped.store(true, Release);
+}
+
+fn wait_for_stopped(stopped: &AtomicBool) {
+    while !stopped.load(Acquire) {
+        thread::yield_now();
+    }
+}
+
 // 진행 상황 보고
 pub fn example2() {
     let num_done = AtomicUsize::new(0);
@@ -52,11 +96,53 @@ pub fn example2() {
     println!("Done!");
 }
 
+// 진행 상황 보고 (Condvar)
+// 진행 상황은 여전히 AtomicUsize 로 읽고, Condvar 는 모니터 스레드를 깨우는 데만 쓴다.
+pub fn example2_condvar() {
+    report_with_condvar(100, process_item);
+    println!("Done!");
+}
+
+// 모니터가 마지막으로 확인한 완료 개수를 돌려준다.
+fn report_with_condvar(items: usize, process: impl Fn(usize) + Sync) -> usize {
+    let num_done = AtomicUsize::new(0);
+    // 모니터가 마지막으로 출력한 값
+    let reported = Mutex::new(usize::MAX);
+    let progressed = Condvar::new();
+
+    thread::scope(|s| {
+        s.spawn(|| {
+            for i in 0..items {
+                process(i);
+                num_done.store(i + 1, Relaxed);
+                // 잠금을 한 번 잡았다 놓아서, 모니터가 조건을 확인한 뒤 잠들기 전에 알리는 일이 없게 한다.
+                drop(reported.lock().unwrap());
+                progressed.notify_one();
+            }
+        });
+
+        let mut reported = reported.lock().unwrap();
+        loop {
+            let n = num_done.load(Relaxed);
+            if n == items {
+                return n;
+            }
+            println!("Working.. {n}/{items} done");
+            *reported = n;
+            reported = progressed
+                .wait_while(reported, |reported| num_done.load(Relaxed) == *reported)
+                .unwrap();
+        }
+    })
+}
 
 // 동기화
+// 작업이 끝났다는 것은 ShutdownToken 으로 알린다. trigger 가 등록된 메인 스레드를 깨워 준다.
 pub fn example3() {
     let num_done = AtomicUsize::new(0);
+    let done = ShutdownToken::new();
     let main_thread = thread::current();
+    done.register(main_thread.clone());
 
     thread::scope(|s| {
         s.spawn(|| {
@@ -65,13 +151,11 @@ pub fn example3() {
                 num_done.store(i + 1, Relaxed);
                 main_thread.unpark();
             }
+            done.trigger();
         });
 
-        loop {
+        while !done.is_triggered() {
             let n = num_done.load(Relaxed);
-            if n == 100 {
-                break;
-            }
             println!("Working.. {n}/100 done");
             thread::park_timeout(Duration::from_secs(1));
         }
@@ -88,11 +172,40 @@ pub fn fetch_add_example() {
     println!("c = {c}");
 }
 
+// fetch_add 는 넘치면 2의 보수로 감싸서 0 부터 다시 센다. (일반 + 와 달리 debug 빌드에서도 panic 하지 않는다.)
+pub fn fetch_add_overflow_demo() {
+    let (wrapped, checked) = overflow_race(250, 4, 3);
+    println!("fetch_add:   250 + 12 = {wrapped}");
+    println!("checked_inc: 250 + 12 = {checked}");
+}
+
+// 네 스레드가 각각 n 번씩 1 을 더한 뒤 (fetch_add 의 결과, checked_inc 의 결과) 를 돌려준다.
+fn overflow_race(start: u8, threads: usize, n: usize) -> (u8, u8) {
+    let naive = AtomicU8::new(start);
+    let checked = AtomicU8::new(start);
+    thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(|| {
+                for _ in 0..n {
+                    naive.fetch_add(1, Relaxed);
+                    checked_inc(&checked);
+                }
+            });
+        }
+    });
+    (naive.into_inner(), checked.into_inner())
+}
+
+// u8::MAX 에서 멈추는 증가. 더할 수 없으면 값을 바꾸지 않고 false 를 돌려준다.
+pub fn checked_inc(a: &AtomicU8) -> bool {
+    a.fetch_update(Relaxed, Relaxed, |n| n.checked_add(1)).is_ok()
+}
+
 pub fn example4() {
     let num_dome = &AtomicUsize::new(0);
 
     thread::scope(|s| {
-        for t in 0..4 {
+        for _ in 0..4 {
             s.spawn(move || {
                 for i in 0..25 {
                     process_item(i);
@@ -112,7 +225,106 @@ pub fn example4() {
     });
 }
 
+// 통계 보고
+pub fn example4_stats() {
+    let stats = ConcurrentStats::new();
+    process_with_stats(&stats, 4, 25);
+    println!("Done! min {:?} ms, max {:?} ms", stats.min(), stats.max());
+}
+
+// 개수, 최솟값, 최댓값은 stats 에 기록하고 (처리한 항목 수, 누적 처리 시간(ms)) 을 돌려준다.
+fn process_with_stats(stats: &ConcurrentStats, threads: usize, items_per_thread: usize) -> (usize, u64) {
+    let total = (threads * items_per_thread) as u64;
+    let total_time = &AtomicU64::new(0);
+    let main_thread = &thread::current();
+
+    thread::scope(|s| {
+        for _ in 0..threads {
+            s.spawn(move || {
+                for i in 0..items_per_thread {
+                    let start = Instant::now();
+                    process_item(i);
+                    let time_taken = start.elapsed().as_millis() as u64;
+                    total_time.fetch_add(time_taken, Relaxed);
+                    stats.record(time_taken);
+                    main_thread.unpark();
+                }
+            });
+        }
+
+        loop {
+            let total_time = total_time.load(Relaxed);
+            // count 와 min/max 를 따로 읽으면 n == 0 인데 min/max 가 Some 일 수 있으므로 한 번에 읽는다.
+            let snapshot = stats.snapshot();
+            let n = snapshot.count;
+            if n == total {
+                break;
+            }
+            match (n, snapshot.min, snapshot.max) {
+                (1.., Some(min), Some(max)) => println!(
+                    "Working.. {n}/{total} done, avg {} ms, min {min} ms, max {max} ms",
+                    total_time / n
+                ),
+                _ => println!("Working.. nothing done yet."),
+            }
+            thread::park_timeout(Duration::from_secs(1));
+        }
+    });
+
+    (stats.count() as usize, total_time.load(Relaxed))
+}
 
 fn process_item(i: usize) {
     thread::sleep(Duration::from_millis(i as u64 * 50));
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicU8};
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use std::time::Duration;
+    use crate::ch2::atomic::{checked_inc, overflow_race, process_with_stats, report_with_condvar, run_until_stopped, wait_for_stopped};
+    use crate::ch2::stats::ConcurrentStats;
+
+    #[test]
+    fn stop_ack_test() {
+        let stop = AtomicBool::new(false);
+        let stopped = AtomicBool::new(false);
+        thread::scope(|s| {
+            s.spawn(|| run_until_stopped(&stop, &stopped));
+            assert!(!stopped.load(Relaxed));
+            stop.store(true, Relaxed);
+            wait_for_stopped(&stopped);
+        });
+        assert!(stopped.load(Relaxed));
+    }
+
+    #[test]
+    fn condvar_report_test() {
+        let n = report_with_condvar(10, |_| thread::sleep(Duration::from_millis(5)));
+        assert_eq!(n, 10);
+    }
+
+    #[test]
+    fn stats_test() {
+        let stats = ConcurrentStats::new();
+        let (num_done, total_time) = process_with_stats(&stats, 2, 4);
+        assert_eq!(num_done, 8);
+        assert!(total_time > 0);
+        // 항목 i 는 i * 50ms 동안 잔다.
+        assert!(stats.min().unwrap() < 50);
+        assert!(stats.max().unwrap() >= 150);
+    }
+
+    #[test]
+    fn fetch_add_wraps_checked_saturates() {
+        // 250 + 12 = 262 = 256 + 6
+        assert_eq!(overflow_race(250, 4, 3), (6, u8::MAX));
+
+        let a = AtomicU8::new(u8::MAX - 1);
+        assert!(checked_inc(&a));
+        assert!(!checked_inc(&a));
+        assert_eq!(a.load(Relaxed), u8::MAX);
+    }
+}
@@ -0,0 +1,111 @@
+// CAS 루프와 spin 대기에서 쓰는 지수 backoff.
+// 실패할 때마다 spin 횟수를 2^step 으로 늘리고, snooze 는 SPIN_LIMIT 를 넘으면 yield 로 바꾼다.
+// spin 은 yield 하지 않으므로 곧 성공할 CAS 재시도에, snooze 는 다른 스레드를 기다릴 때 쓴다.
+
+use std::cell::Cell;
+use std::thread;
+
+const SPIN_LIMIT: u32 = 6;
+
+pub struct Backoff {
+    step: Cell<u32>,
+}
+
+impl Backoff {
+    pub const fn new() -> Self {
+        Self { step: Cell::new(0) }
+    }
+
+    pub fn reset(&self) {
+        self.step.set(0);
+    }
+
+    // CAS 가 실패했을 때. 다른 스레드와의 경쟁을 줄이도록 잠깐 기다린다.
+    pub fn spin(&self) {
+        for _ in 0..1 << self.step.get().min(SPIN_LIMIT) {
+            std::hint::spin_loop();
+        }
+        if self.step.get() <= SPIN_LIMIT {
+            self.step.set(self.step.get() + 1);
+        }
+    }
+
+    // 다른 스레드가 무언가 해 주기를 기다릴 때. 2^SPIN_LIMIT 번까지 spin 한 뒤로는 매번 CPU 를 양보한다.
+    pub fn snooze(&self) {
+        if self.step.get() <= SPIN_LIMIT {
+            for _ in 0..1 << self.step.get() {
+                std::hint::spin_loop();
+            }
+            self.step.set(self.step.get() + 1);
+        } else {
+            thread::yield_now();
+        }
+    }
+
+    // 더 spin 하지 않고 yield 하는 단계에 들어섰는지. crossbeam 의 Backoff 와 같은 이름이다.
+    // true 가 되면 계속 snooze 하기보다 park 처럼 잠드는 방법으로 바꾸는 것이 좋다.
+    pub fn is_completed(&self) -> bool {
+        self.step.get() > SPIN_LIMIT
+    }
+
+    // is_completed 의 다른 이름
+    pub fn is_yielding(&self) -> bool {
+        self.is_completed()
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use crate::ch2::backoff::{Backoff, SPIN_LIMIT};
+
+    #[test]
+    fn cas_loop_converges() {
+        let counter = AtomicU64::new(0);
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..1000 {
+                        let backoff = Backoff::new();
+                        let mut current = counter.load(Relaxed);
+                        while let Err(v) = counter.compare_exchange_weak(current, current + 1, Relaxed, Relaxed) {
+                            current = v;
+                            backoff.spin();
+                        }
+                    }
+                });
+            }
+        });
+        assert_eq!(counter.load(Relaxed), 8000);
+    }
+
+    #[test]
+    fn snooze_escalates() {
+        let backoff = Backoff::new();
+        for _ in 0..=SPIN_LIMIT {
+            assert!(!backoff.is_completed());
+            backoff.snooze();
+        }
+        assert!(backoff.is_completed());
+        // yield 단계에서는 더 올라가지 않는다.
+        backoff.snooze();
+        assert_eq!(backoff.step.get(), SPIN_LIMIT + 1);
+        assert!(backoff.is_yielding());
+        backoff.reset();
+        assert!(!backoff.is_completed());
+
+        // spin 은 step 이 끝까지 올라가도 yield 하지 않고 2^SPIN_LIMIT 번 spin 한다.
+        for _ in 0..100 {
+            backoff.spin();
+        }
+        assert_eq!(backoff.step.get(), SPIN_LIMIT + 1);
+    }
+}
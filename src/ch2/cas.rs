@@ -0,0 +1,83 @@
+// compare-and-exchange 루프
+// compare_exchange_weak 는 값이 같아도 가끔 실패할 수 있지만, 어차피 루프 안에서 다시 시도하므로 문제가 없다.
+// 대신 LL/SC 를 쓰는 ARM 같은 플랫폼에서는 compare_exchange 보다 더 가벼운 코드가 된다.
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::Relaxed;
+use crate::ch2::backoff::Backoff;
+
+// AtomicU64::fetch_update 를 직접 구현한 것.
+// f 가 None 을 돌려주면 멈추고 Err(현재 값) 을, 성공하면 Ok(이전 값) 을 돌려준다.
+pub fn fetch_update_spin(a: &AtomicU64, mut f: impl FnMut(u64) -> Option<u64>) -> Result<u64, u64> {
+    let backoff = Backoff::new();
+    let mut current = a.load(Relaxed);
+    loop {
+        let new = match f(current) {
+            Some(new) => new,
+            None => return Err(current),
+        };
+        match a.compare_exchange_weak(current, new, Relaxed, Relaxed) {
+            Ok(v) => return Ok(v),
+            Err(v) => {
+                current = v;
+                backoff.spin();
+            }
+        }
+    }
+}
+
+// a 를 적어도 val 이 되도록 올리고, 이전 값을 돌려준다.
+pub fn atomic_max(a: &AtomicU64, val: u64) -> u64 {
+    match fetch_update_spin(a, |current| (current < val).then_some(val)) {
+        Ok(previous) | Err(previous) => previous,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use crate::ch2::cas::{atomic_max, fetch_update_spin};
+
+    // 테스트용 xorshift 난수
+    fn random_values(seed: u64, n: usize) -> Vec<u64> {
+        let mut x = seed;
+        (0..n)
+            .map(|_| {
+                x ^= x << 13;
+                x ^= x >> 7;
+                x ^= x << 17;
+                x
+            })
+            .collect()
+    }
+
+    #[test]
+    fn atomic_max_test() {
+        let max = AtomicU64::new(0);
+        let values: Vec<Vec<u64>> = (1..=8).map(|seed| random_values(seed, 1000)).collect();
+
+        thread::scope(|s| {
+            for values in &values {
+                let max = &max;
+                s.spawn(move || {
+                    for &v in values {
+                        atomic_max(max, v);
+                    }
+                });
+            }
+        });
+
+        assert_eq!(max.load(Relaxed), *values.iter().flatten().max().unwrap());
+    }
+
+    #[test]
+    fn fetch_update_spin_test() {
+        let a = AtomicU64::new(5);
+        assert_eq!(fetch_update_spin(&a, |v| Some(v * 2)), Ok(5));
+        assert_eq!(fetch_update_spin(&a, |_| None), Err(10));
+        assert_eq!(atomic_max(&a, 3), 10);
+        assert_eq!(a.load(Relaxed), 10);
+    }
+}
@@ -0,0 +1,96 @@
+// 넘치지 않는 카운터
+// fetch_add 는 한 번의 atomic 연산이라 빠르지만, u64::MAX 에서 더하면 0 으로 돌아간다.
+// fetch_update 로 검사한 뒤 더하면 넘치지 않지만, 다른 스레드와 경쟁하면 CAS 를 다시 시도해야 해서 더 느리다.
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::Relaxed;
+
+// fetch_add/fetch_sub 를 그대로 쓰는 카운터. 넘치면 wrap 된다.
+pub struct WrappingCounter {
+    value: AtomicU64,
+}
+
+impl WrappingCounter {
+    pub const fn new(value: u64) -> Self {
+        Self { value: AtomicU64::new(value) }
+    }
+
+    pub fn inc(&self) -> u64 {
+        self.value.fetch_add(1, Relaxed)
+    }
+
+    pub fn dec(&self) -> u64 {
+        self.value.fetch_sub(1, Relaxed)
+    }
+
+    pub fn get(&self) -> u64 {
+        self.value.load(Relaxed)
+    }
+}
+
+// u64::MAX 와 0 에서 멈추는 카운터.
+pub struct SaturatingCounter {
+    value: AtomicU64,
+}
+
+impl SaturatingCounter {
+    pub const fn new(value: u64) -> Self {
+        Self { value: AtomicU64::new(value) }
+    }
+
+    // 이전 값을 돌려준다. 이미 u64::MAX 이면 바꾸지 않는다.
+    pub fn inc(&self) -> u64 {
+        match self.value.fetch_update(Relaxed, Relaxed, |v| v.checked_add(1)) {
+            Ok(v) | Err(v) => v,
+        }
+    }
+
+    // 이전 값을 돌려준다. 이미 0 이면 바꾸지 않는다.
+    pub fn dec(&self) -> u64 {
+        match self.value.fetch_update(Relaxed, Relaxed, |v| v.checked_sub(1)) {
+            Ok(v) | Err(v) => v,
+        }
+    }
+
+    pub fn get(&self) -> u64 {
+        self.value.load(Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use crate::ch2::counter::{SaturatingCounter, WrappingCounter};
+
+    #[test]
+    fn saturates_at_max() {
+        let counter = SaturatingCounter::new(u64::MAX - 100);
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..1000 {
+                        assert_ne!(counter.inc(), 0);
+                    }
+                });
+            }
+        });
+        assert_eq!(counter.get(), u64::MAX);
+    }
+
+    #[test]
+    fn saturates_at_zero() {
+        let counter = SaturatingCounter::new(1);
+        assert_eq!(counter.dec(), 1);
+        assert_eq!(counter.dec(), 0);
+        assert_eq!(counter.get(), 0);
+    }
+
+    #[test]
+    fn wrapping_counter_wraps() {
+        let counter = WrappingCounter::new(u64::MAX);
+        counter.inc();
+        assert_eq!(counter.get(), 0);
+        counter.dec();
+        assert_eq!(counter.get(), u64::MAX);
+    }
+}
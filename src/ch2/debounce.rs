@@ -0,0 +1,58 @@
+// 여러 스레드 중 처음 한 번만 통과시키는 플래그
+// 예: 여러 곳에서 동시에 정지 요청이 와도 정리 작업은 한 번만 실행한다.
+// swap 은 하나의 read-modify-write 이므로 false 를 보고 true 로 바꾸는 스레드는 정확히 하나다.
+// AcqRel: 통과한 스레드는 플래그 이전의 작업을 보고(Acquire), 뒤에 오는 스레드는 통과한 스레드의 이전 작업을 본다(Release).
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::{AcqRel, Acquire};
+
+pub struct OnceFlag {
+    set: AtomicBool,
+}
+
+impl OnceFlag {
+    pub const fn new() -> Self {
+        Self { set: AtomicBool::new(false) }
+    }
+
+    // 처음 호출한 스레드만 true 를 돌려받는다.
+    pub fn try_set(&self) -> bool {
+        !self.set.swap(true, AcqRel)
+    }
+
+    pub fn is_set(&self) -> bool {
+        self.set.load(Acquire)
+    }
+}
+
+impl Default for OnceFlag {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use crate::ch2::debounce::OnceFlag;
+
+    #[test]
+    fn exactly_one_winner() {
+        let flag = OnceFlag::new();
+        let winners = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for _ in 0..16 {
+                s.spawn(|| {
+                    if flag.try_set() {
+                        winners.fetch_add(1, Relaxed);
+                    }
+                });
+            }
+        });
+        assert_eq!(winners.load(Relaxed), 1);
+        assert!(flag.is_set());
+        assert!(!flag.try_set());
+    }
+}
@@ -0,0 +1,97 @@
+// AtomicU32 하나를 32개짜리 비트 집합으로 쓴다.
+// fetch_or / fetch_and 는 다른 비트를 건드리지 않고 한 비트만 바꾸므로, 여러 스레드가 서로 다른 비트를 동시에 바꿔도 잃어버리는 갱신이 없다.
+
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed};
+
+pub struct AtomicFlags {
+    bits: AtomicU32,
+}
+
+impl AtomicFlags {
+    pub const fn new() -> Self {
+        Self { bits: AtomicU32::new(0) }
+    }
+
+    pub fn set(&self, bit: u32) {
+        self.bits.fetch_or(mask(bit), AcqRel);
+    }
+
+    pub fn clear(&self, bit: u32) {
+        self.bits.fetch_and(!mask(bit), AcqRel);
+    }
+
+    pub fn test(&self, bit: u32) -> bool {
+        self.bits.load(Acquire) & mask(bit) != 0
+    }
+
+    // 비트가 꺼져 있을 때만 켜고, 이 호출이 실제로 켰으면 true 를 돌려준다.
+    // 이미 켜져 있으면 fetch_update 가 None 을 받아 아무것도 쓰지 않는다.
+    pub fn set_if_clear(&self, bit: u32) -> bool {
+        let mask = mask(bit);
+        self.bits
+            .fetch_update(AcqRel, Relaxed, |bits| (bits & mask == 0).then_some(bits | mask))
+            .is_ok()
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.bits.load(Acquire)
+    }
+}
+
+impl Default for AtomicFlags {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn mask(bit: u32) -> u32 {
+    assert!(bit < u32::BITS, "bit index out of range");
+    1 << bit
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use crate::ch2::flags::AtomicFlags;
+
+    #[test]
+    fn set_and_clear_from_many_threads() {
+        let flags = AtomicFlags::new();
+        flags.set(31);
+        thread::scope(|s| {
+            for bit in 0..8 {
+                let flags = &flags;
+                s.spawn(move || {
+                    for _ in 0..1000 {
+                        flags.set(bit);
+                    }
+                });
+            }
+            s.spawn(|| flags.clear(31));
+        });
+        assert_eq!(flags.bits(), 0xff);
+        assert!(flags.test(7));
+        assert!(!flags.test(8));
+        assert!(!flags.test(31));
+    }
+
+    #[test]
+    fn set_if_clear_flips_once() {
+        let flags = AtomicFlags::new();
+        let flipped = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    if flags.set_if_clear(3) {
+                        flipped.fetch_add(1, Relaxed);
+                    }
+                });
+            }
+        });
+        assert_eq!(flipped.load(Relaxed), 1);
+        assert_eq!(flags.bits(), 1 << 3);
+    }
+}
@@ -0,0 +1,117 @@
+// 세대(generation) 카운터
+// writer 가 데이터를 바꿀 때마다 bump 하고, reader 는 기억해 둔 세대와 비교해서 캐시가 낡았는지 안다.
+// 값이 바뀌었다는 것만 알면 되는 경우에는 Relaxed 로도 충분하다.
+// 하지만 바뀐 세대를 본 뒤 그 세대의 데이터까지 보려면 bump 는 Release, current 는 Acquire 여야 한다.
+// 그렇지 않으면 새 세대 번호를 보고도 예전 데이터를 읽을 수 있다.
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+
+pub struct Generation {
+    generation: AtomicU64,
+}
+
+impl Generation {
+    pub const fn new() -> Self {
+        Self { generation: AtomicU64::new(0) }
+    }
+
+    // 세대를 하나 올리고 새 세대를 돌려준다.
+    // u64 는 1ns 마다 올려도 수백 년이 걸려야 넘치므로 wraparound 는 걱정하지 않는다.
+    pub fn bump(&self) -> u64 {
+        self.generation.fetch_add(1, Release) + 1
+    }
+
+    pub fn current(&self) -> u64 {
+        self.generation.load(Acquire)
+    }
+
+    pub fn changed_since(&self, old: u64) -> bool {
+        self.current() != old
+    }
+}
+
+impl Default for Generation {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Generation 을 이용한 캐시 무효화 예제.
+// source 를 바꾼 뒤 bump 하면, reader 는 다음 get 에서 새 값을 읽어 온다.
+pub struct Source {
+    value: AtomicU64,
+    generation: Generation,
+}
+
+impl Source {
+    pub const fn new(value: u64) -> Self {
+        Self { value: AtomicU64::new(value), generation: Generation::new() }
+    }
+
+    pub fn set(&self, value: u64) {
+        self.value.store(value, Relaxed);
+        // Release: 위의 store 가 새 세대와 함께 보이도록 한다.
+        self.generation.bump();
+    }
+}
+
+pub struct Cache<'a> {
+    source: &'a Source,
+    generation: u64,
+    value: u64,
+}
+
+impl<'a> Cache<'a> {
+    pub fn new(source: &'a Source) -> Self {
+        let generation = source.generation.current();
+        Self { source, generation, value: source.value.load(Relaxed) }
+    }
+
+    // 캐시가 낡았으면 다시 읽어 오고, 값을 돌려준다.
+    pub fn get(&mut self) -> u64 {
+        let current = self.source.generation.current();
+        if current != self.generation {
+            // Acquire 로 current 를 읽었으므로 적어도 그 세대의 값이 보인다.
+            self.value = self.source.value.load(Relaxed);
+            self.generation = current;
+        }
+        self.value
+    }
+
+    pub fn is_stale(&self) -> bool {
+        self.source.generation.changed_since(self.generation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use crate::ch2::generation::{Cache, Generation, Source};
+
+    #[test]
+    fn bump_and_changed_since() {
+        let generation = Generation::new();
+        let old = generation.current();
+        assert!(!generation.changed_since(old));
+        assert_eq!(generation.bump(), old + 1);
+        assert!(generation.changed_since(old));
+    }
+
+    #[test]
+    fn reader_sees_new_value_after_bump() {
+        let source = Source::new(1);
+        thread::scope(|s| {
+            let mut cache = Cache::new(&source);
+            assert_eq!(cache.get(), 1);
+
+            s.spawn(|| source.set(42));
+
+            while !cache.is_stale() {
+                thread::yield_now();
+            }
+            assert_eq!(cache.get(), 42);
+            assert!(!cache.is_stale());
+        });
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering::Relaxed;
+
+static NEXT_ID: AtomicU32 = AtomicU32::new(0);
+
+// ID 할당
+pub fn allocate_new_id() -> u32 {
+    NEXT_ID.fetch_add(1, Relaxed)
+}
+
+// u32::MAX 에 도달하면 감싸서 0 으로 돌아가는 대신 None 을 돌려준다.
+pub fn allocate_new_id_checked() -> Option<u32> {
+    checked_increment(&NEXT_ID)
+}
+
+fn checked_increment(next_id: &AtomicU32) -> Option<u32> {
+    next_id.fetch_update(Relaxed, Relaxed, |n| n.checked_add(1)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::atomic::AtomicU32;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use crate::ch2::id::{allocate_new_id, allocate_new_id_checked, checked_increment};
+
+    #[test]
+    fn distinct_ids() {
+        let ids: Vec<u32> = thread::scope(|s| {
+            let handles: Vec<_> = (0..4)
+                .map(|t| {
+                    s.spawn(move || {
+                        (0..250)
+                            .map(|_| if t % 2 == 0 { allocate_new_id() } else { allocate_new_id_checked().unwrap() })
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect();
+            handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+        });
+        assert_eq!(ids.iter().collect::<HashSet<_>>().len(), 1000);
+    }
+
+    #[test]
+    fn checked_refuses_to_wrap() {
+        // fetch_add 는 u32::MAX 다음에 조용히 0 으로 돌아가므로, 이미 나눠준 ID 를 다시 나눠주게 된다.
+        let naive = AtomicU32::new(u32::MAX);
+        naive.fetch_add(1, Relaxed);
+        assert_eq!(naive.into_inner(), 0);
+
+        let next_id = AtomicU32::new(u32::MAX - 1);
+        assert_eq!(checked_increment(&next_id), Some(u32::MAX - 1));
+        assert_eq!(checked_increment(&next_id), None);
+        assert_eq!(checked_increment(&next_id), None);
+    }
+}
@@ -0,0 +1,50 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::Relaxed;
+
+// 0 은 "아직 초기화되지 않음" 을 뜻한다.
+static KEY: AtomicU64 = AtomicU64::new(0);
+
+// 지연 초기화
+// 두 스레드가 동시에 키를 만들 수도 있지만, 저장에 성공하는 것은 하나뿐이고 나머지는 그 값을 쓴다.
+pub fn get_key() -> u64 {
+    let key = KEY.load(Relaxed);
+    if key != 0 {
+        return key;
+    }
+    let new_key = generate_random_key();
+    match KEY.compare_exchange(0, new_key, Relaxed, Relaxed) {
+        Ok(_) => new_key,
+        Err(k) => k,
+    }
+}
+
+// 0 은 센티널로 예약되어 있으므로 0 이 나오면 다시 만든다.
+fn generate_random_key() -> u64 {
+    loop {
+        let key = RandomState::new().build_hasher().finish();
+        if key != 0 {
+            return key;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use crate::ch2::lazy::get_key;
+
+    #[test]
+    fn stable_key() {
+        let keys: Vec<u64> = thread::scope(|s| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| s.spawn(|| (0..100).map(|_| get_key()).collect::<Vec<_>>()))
+                .collect();
+            handles.into_iter().flat_map(|h| h.join().unwrap()).collect()
+        });
+        let key = get_key();
+        assert_ne!(key, 0);
+        assert!(keys.iter().all(|&k| k == key));
+    }
+}
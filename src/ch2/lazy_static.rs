@@ -0,0 +1,123 @@
+// 처음 접근할 때 초기화되는 값
+// OnceInit 과 달리 초기화 함수를 값과 함께 담고 있어서, 구조체 필드나 static 으로 그대로 쓸 수 있다.
+// 초기화할 스레드는 UNINIT -> RUNNING CAS 로 정하고, 다른 스레드는 DONE 을 Acquire 로 볼 때까지 기다린다.
+
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::ops::Deref;
+use std::sync::atomic::AtomicU8;
+use std::sync::atomic::Ordering::{Acquire, Release};
+use std::thread;
+use crate::ch9::guard::defer;
+
+const UNINIT: u8 = 0;
+const RUNNING: u8 = 1;
+const DONE: u8 = 2;
+// 초기화 함수가 panic 했다. 함수는 이미 소비되었으므로 다시 시도할 수 없다.
+const POISONED: u8 = 3;
+
+pub struct Lazy<T, F = fn() -> T> {
+    state: AtomicU8,
+    init: UnsafeCell<Option<F>>,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+unsafe impl<T, F> Sync for Lazy<T, F> where T: Send + Sync, F: Send {}
+
+impl<T, F: FnOnce() -> T> Lazy<T, F> {
+    pub const fn new(init: F) -> Self {
+        Self {
+            state: AtomicU8::new(UNINIT),
+            init: UnsafeCell::new(Some(init)),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    pub fn force(&self) -> &T {
+        loop {
+            match self.state.compare_exchange(UNINIT, RUNNING, Acquire, Acquire) {
+                Ok(_) => {
+                    let poison = defer(|| self.state.store(POISONED, Release));
+                    // RUNNING 으로 바꾼 스레드만 init 에 접근한다.
+                    let init = unsafe { (*self.init.get()).take().unwrap() };
+                    let value = init();
+                    poison.cancel();
+                    unsafe { (*self.value.get()).write(value) };
+                    self.state.store(DONE, Release);
+                }
+                Err(RUNNING) => thread::yield_now(),
+                Err(POISONED) => panic!("Lazy instance has been poisoned"),
+                Err(_) => {}
+            }
+            // Acquire: 초기화한 스레드가 쓴 value 가 보인다.
+            if self.state.load(Acquire) == DONE {
+                return unsafe { (*self.value.get()).assume_init_ref() };
+            }
+        }
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        if self.state.load(Acquire) == DONE {
+            Some(unsafe { (*self.value.get()).assume_init_ref() })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T, F: FnOnce() -> T> Deref for Lazy<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.force()
+    }
+}
+
+impl<T, F> Drop for Lazy<T, F> {
+    fn drop(&mut self) {
+        if *self.state.get_mut() == DONE {
+            unsafe { self.value.get_mut().assume_init_drop() }
+        }
+        // 아직 쓰지 않은 init 은 Option 이 drop 한다.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use std::time::Duration;
+    use crate::ch2::lazy_static::Lazy;
+
+    #[test]
+    fn initialized_once_across_threads() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let lazy = Lazy::new(|| {
+            CALLS.fetch_add(1, Relaxed);
+            thread::sleep(Duration::from_millis(10));
+            vec![1, 2, 3]
+        });
+        assert!(lazy.get().is_none());
+
+        let addresses: Vec<usize> = thread::scope(|s| {
+            let handles: Vec<_> = (0..8)
+                .map(|_| s.spawn(|| {
+                    assert_eq!(*lazy, [1, 2, 3]);
+                    lazy.as_ptr() as usize
+                }))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+
+        assert_eq!(CALLS.load(Relaxed), 1);
+        assert!(addresses.windows(2).all(|w| w[0] == w[1]));
+    }
+
+    #[test]
+    fn usable_as_static() {
+        static GREETING: Lazy<String> = Lazy::new(|| String::from("hello"));
+        assert_eq!(GREETING.len(), 5);
+        assert_eq!(*GREETING, "hello");
+    }
+}
@@ -1 +1,17 @@
-pub mod atomic;
\ No newline at end of file
+pub mod atomic;
+pub mod backoff;
+pub mod cas;
+pub mod counter;
+pub mod debounce;
+pub mod flags;
+pub mod generation;
+pub mod id;
+pub mod lazy;
+pub mod lazy_static;
+pub mod notify;
+pub mod once;
+pub mod ratelimit;
+pub mod scoped_sum;
+pub mod sharded;
+pub mod shutdown;
+pub mod stats;
\ No newline at end of file
@@ -0,0 +1,80 @@
+// 최대 32개 worker 의 완료 여부를 AtomicU32 하나로 모은다. worker i 는 i 번째 비트를 켠다.
+// Release/Acquire: all_done 이 true 를 본 coordinator 는 worker 들이 끝내기 전에 한 작업을 모두 본다.
+
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering::{Acquire, Release};
+
+pub struct DoneSet {
+    bits: AtomicU32,
+}
+
+impl DoneSet {
+    pub const fn new() -> Self {
+        Self { bits: AtomicU32::new(0) }
+    }
+
+    pub fn mark_done(&self, worker: u32) {
+        assert!(worker < u32::BITS, "DoneSet supports up to 32 workers");
+        self.bits.fetch_or(1 << worker, Release);
+    }
+
+    // 0..n 번 worker 가 모두 끝났는지
+    pub fn all_done(&self, n: u32) -> bool {
+        assert!(n <= u32::BITS, "DoneSet supports up to 32 workers");
+        let mask = u32::MAX.checked_shr(u32::BITS - n).unwrap_or(0);
+        self.bits.load(Acquire) & mask == mask
+    }
+
+    pub fn bits(&self) -> u32 {
+        self.bits.load(Acquire)
+    }
+}
+
+impl Default for DoneSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use crate::ch2::notify::DoneSet;
+
+    #[test]
+    fn coordinator_waits_for_all() {
+        const WORKERS: u32 = 6;
+        let done = DoneSet::new();
+        let results: [AtomicU64; WORKERS as usize] = Default::default();
+        thread::scope(|s| {
+            for i in 0..WORKERS {
+                let (done, results) = (&done, &results);
+                s.spawn(move || {
+                    results[i as usize].store(i as u64 * 10, Relaxed);
+                    done.mark_done(i);
+                });
+            }
+            while !done.all_done(WORKERS) {
+                thread::yield_now();
+            }
+            // mark_done 의 Release 덕분에 Relaxed 로 쓴 결과도 모두 보인다.
+            for (i, r) in results.iter().enumerate() {
+                assert_eq!(r.load(Relaxed), i as u64 * 10);
+            }
+        });
+        assert_eq!(done.bits(), 0b11_1111);
+    }
+
+    #[test]
+    fn edge_counts() {
+        let done = DoneSet::new();
+        assert!(done.all_done(0));
+        assert!(!done.all_done(1));
+        for i in 0..32 {
+            done.mark_done(i);
+        }
+        assert!(done.all_done(32));
+    }
+}
@@ -0,0 +1,115 @@
+use std::cell::UnsafeCell;
+use std::mem::MaybeUninit;
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+use crate::ch9::futex::{wait, wake_all};
+use crate::ch9::guard::defer;
+
+const UNINIT: u32 = 0;
+const RUNNING: u32 = 1;
+const DONE: u32 = 2;
+
+pub struct OnceInit<T> {
+    state: AtomicU32,
+    value: UnsafeCell<MaybeUninit<T>>,
+}
+
+unsafe impl<T> Sync for OnceInit<T> where T: Send + Sync {}
+
+impl<T> OnceInit<T> {
+    pub const fn new() -> Self {
+        Self {
+            state: AtomicU32::new(UNINIT),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+        }
+    }
+
+    pub fn get(&self) -> Option<&T> {
+        if self.state.load(Acquire) == DONE {
+            Some(unsafe { (*self.value.get()).assume_init_ref() })
+        } else {
+            None
+        }
+    }
+
+    pub fn get_or_init(&self, f: impl FnOnce() -> T) -> &T {
+        let mut f = Some(f);
+        loop {
+            match self.state.compare_exchange(UNINIT, RUNNING, Acquire, Acquire) {
+                Ok(_) => {
+                    // f 가 panic 하면 상태를 UNINIT 으로 되돌려서 다른 스레드가 다시 시도할 수 있게 한다.
+                    let reset = defer(|| {
+                        self.state.store(UNINIT, Relaxed);
+                        wake_all(&self.state);
+                    });
+                    let value = (f.take().unwrap())();
+                    reset.cancel();
+                    unsafe { (*self.value.get()).write(value) };
+                    self.state.store(DONE, Release);
+                    wake_all(&self.state);
+                }
+                Err(RUNNING) => wait(&self.state, RUNNING),
+                Err(_) => {}
+            }
+            if let Some(value) = self.get() {
+                return value;
+            }
+        }
+    }
+}
+
+impl<T> Default for OnceInit<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Drop for OnceInit<T> {
+    fn drop(&mut self) {
+        if *self.state.get_mut() == DONE {
+            unsafe { self.value.get_mut().assume_init_drop() }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use std::time::Duration;
+    use crate::ch2::once::OnceInit;
+
+    #[test]
+    fn once_init_test() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+        let once = OnceInit::new();
+
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    let value = once.get_or_init(|| {
+                        CALLS.fetch_add(1, Relaxed);
+                        thread::sleep(Duration::from_millis(10));
+                        String::from("hello")
+                    });
+                    assert_eq!(value, "hello");
+                });
+            }
+        });
+
+        assert_eq!(CALLS.load(Relaxed), 1);
+    }
+
+    #[test]
+    fn panic_resets_state() {
+        let once = OnceInit::new();
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            once.get_or_init(|| panic!("init failed"));
+        }));
+        assert!(result.is_err());
+        assert_eq!(once.get(), None);
+        assert_eq!(*once.get_or_init(|| 123), 123);
+    }
+}
@@ -0,0 +1,68 @@
+// CAS 루프를 실제로 쓰는 예: 토큰 버킷
+// 남은 토큰이 충분할 때만 빼야 하므로 fetch_sub 하나로는 안 되고 fetch_update 가 필요하다.
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::Relaxed;
+
+pub struct TokenBucket {
+    tokens: AtomicU64,
+}
+
+impl TokenBucket {
+    pub const fn new(tokens: u64) -> Self {
+        Self { tokens: AtomicU64::new(tokens) }
+    }
+
+    // 토큰이 n 개 이상 남아 있으면 n 개를 가져가고 true 를 돌려준다.
+    // 토큰 수 외에 다른 데이터를 보호하지 않으므로 Relaxed 로 충분하다.
+    pub fn try_acquire(&self, n: u64) -> bool {
+        self.tokens
+            .fetch_update(Relaxed, Relaxed, |tokens| tokens.checked_sub(n))
+            .is_ok()
+    }
+
+    pub fn refill(&self, n: u64) {
+        self.tokens.fetch_add(n, Relaxed);
+    }
+
+    pub fn available(&self) -> u64 {
+        self.tokens.load(Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use crate::ch2::ratelimit::TokenBucket;
+
+    #[test]
+    fn acquired_never_exceeds_supply() {
+        let bucket = TokenBucket::new(100);
+        let acquired = AtomicU64::new(0);
+
+        thread::scope(|s| {
+            for n in 1..=4 {
+                let (bucket, acquired) = (&bucket, &acquired);
+                s.spawn(move || {
+                    for _ in 0..100 {
+                        if bucket.try_acquire(n) {
+                            acquired.fetch_add(n, Relaxed);
+                        }
+                    }
+                });
+            }
+            s.spawn(|| {
+                for _ in 0..10 {
+                    bucket.refill(5);
+                    thread::yield_now();
+                }
+            });
+        });
+
+        let acquired = acquired.load(Relaxed);
+        assert!(acquired <= 100 + 50);
+        assert_eq!(acquired + bucket.available(), 100 + 50);
+    }
+}
@@ -0,0 +1,82 @@
+// 자주 더하는 값을 스레드가 가진 Cell 에 모아 두었다가, flush 나 drop 할 때 한 번의 fetch_add 로 공유 합계에 더한다.
+// 공유 AtomicU64 에 매번 fetch_add 하면 모든 스레드가 같은 캐시 라인을 두고 경쟁하지만, 이렇게 하면 경쟁은 flush 할 때만 생긴다.
+// 대신 다른 스레드는 flush 전까지 합계에서 이 스레드의 몫을 보지 못한다.
+
+use std::cell::Cell;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::Relaxed;
+
+// Cell 을 가지므로 Sync 가 아니고, 만든 스레드 안에서만 더할 수 있다.
+pub struct ScopedAccumulator<'a> {
+    total: &'a AtomicU64,
+    local: Cell<u64>,
+}
+
+impl<'a> ScopedAccumulator<'a> {
+    pub fn new(total: &'a AtomicU64) -> Self {
+        Self { total, local: Cell::new(0) }
+    }
+
+    pub fn add(&self, n: u64) {
+        self.local.set(self.local.get() + n);
+    }
+
+    // 아직 flush 하지 않은 몫
+    pub fn pending(&self) -> u64 {
+        self.local.get()
+    }
+
+    pub fn flush(&self) {
+        let n = self.local.replace(0);
+        if n != 0 {
+            self.total.fetch_add(n, Relaxed);
+        }
+    }
+}
+
+impl Drop for ScopedAccumulator<'_> {
+    fn drop(&mut self) {
+        self.flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use crate::ch2::scoped_sum::ScopedAccumulator;
+
+    #[test]
+    fn flushes_into_total() {
+        let total = AtomicU64::new(0);
+        thread::scope(|s| {
+            for t in 0..8 {
+                let total = &total;
+                s.spawn(move || {
+                    let acc = ScopedAccumulator::new(total);
+                    for _ in 0..100_000 {
+                        acc.add(1);
+                    }
+                    // 절반은 명시적으로 flush 하고, 나머지는 drop 에 맡긴다.
+                    if t % 2 == 0 {
+                        acc.flush();
+                        assert_eq!(acc.pending(), 0);
+                    }
+                });
+            }
+        });
+        assert_eq!(total.load(Relaxed), 800_000);
+    }
+
+    #[test]
+    fn not_visible_before_flush() {
+        let total = AtomicU64::new(0);
+        let acc = ScopedAccumulator::new(&total);
+        acc.add(5);
+        assert_eq!(total.load(Relaxed), 0);
+        assert_eq!(acc.pending(), 5);
+        drop(acc);
+        assert_eq!(total.load(Relaxed), 5);
+    }
+}
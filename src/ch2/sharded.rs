@@ -0,0 +1,107 @@
+// 쓰기가 많은 카운터를 여러 shard 로 나누기
+// 모든 스레드가 하나의 AtomicU64 에 fetch_add 하면 캐시 라인이 코어 사이를 계속 오간다.
+// 스레드마다 다른 shard 에 더하고 읽을 때만 모두 더하면 쓰기끼리 경쟁하지 않는다.
+// shard 들이 같은 캐시 라인에 있으면 여전히 false sharing 이 일어나므로 CachePadded 로 감싼다.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::Relaxed;
+use std::thread;
+use crate::ch7::cache::CachePadded;
+
+const SHARDS: usize = 16;
+
+pub struct ShardedCounter {
+    shards: [CachePadded<AtomicU64>; SHARDS],
+}
+
+thread_local! {
+    // 스레드 id 의 해시로 고른 shard. 매번 해시하지 않도록 스레드마다 한 번만 계산한다.
+    static SHARD: usize = {
+        let mut hasher = DefaultHasher::new();
+        thread::current().id().hash(&mut hasher);
+        hasher.finish() as usize % SHARDS
+    };
+}
+
+impl ShardedCounter {
+    pub const fn new() -> Self {
+        Self {
+            shards: [const { CachePadded::new(AtomicU64::new(0)) }; SHARDS],
+        }
+    }
+
+    pub fn inc(&self) {
+        let shard = SHARD.with(|&shard| shard);
+        self.shards[shard].fetch_add(1, Relaxed);
+    }
+
+    // 다른 스레드가 더하는 중이면 그 순간의 정확한 합은 아닐 수 있다.
+    pub fn sum(&self) -> u64 {
+        self.shards.iter().map(|shard| shard.load(Relaxed)).sum()
+    }
+}
+
+impl Default for ShardedCounter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use std::time::Instant;
+    use crate::ch2::sharded::ShardedCounter;
+
+    #[test]
+    fn sum_of_all_shards() {
+        let counter = ShardedCounter::new();
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..100_000 {
+                        counter.inc();
+                    }
+                });
+            }
+        });
+        assert_eq!(counter.sum(), 800_000);
+    }
+
+    // `cargo test --release sharded_vs_single -- --ignored --nocapture` 로 실행한다.
+    #[test]
+    #[ignore]
+    fn sharded_vs_single() {
+        let single = AtomicU64::new(0);
+        let start = Instant::now();
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..1_000_000 {
+                        single.fetch_add(1, Relaxed);
+                    }
+                });
+            }
+        });
+        println!("single:  {:?}", start.elapsed());
+
+        let sharded = ShardedCounter::new();
+        let start = Instant::now();
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..1_000_000 {
+                        sharded.inc();
+                    }
+                });
+            }
+        });
+        println!("sharded: {:?}", start.elapsed());
+
+        assert_eq!(single.load(Relaxed), sharded.sum());
+    }
+}
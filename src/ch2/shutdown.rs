@@ -0,0 +1,140 @@
+// 여러 곳에서 다시 쓸 수 있는 정지 신호
+// trigger 하기 전의 작업이 is_triggered 로 정지를 확인한 스레드에게 보이도록 Release/Acquire 를 쓴다.
+// 등록된 스레드(또는 Unparker)는 trigger 될 때 unpark 되므로, park 하며 기다려도 신호를 놓치지 않는다.
+
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::{Acquire, Release};
+use std::thread::Thread;
+use crate::ch5::channel::parker::Unparker;
+
+enum Waiter {
+    Thread(Thread),
+    Unparker(Unparker),
+}
+
+impl Waiter {
+    fn wake(self) {
+        match self {
+            Waiter::Thread(t) => t.unpark(),
+            Waiter::Unparker(u) => u.unpark(),
+        }
+    }
+}
+
+pub struct ShutdownToken {
+    triggered: AtomicBool,
+    waiting: Mutex<Vec<Waiter>>,
+}
+
+impl ShutdownToken {
+    pub const fn new() -> Self {
+        Self {
+            triggered: AtomicBool::new(false),
+            waiting: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn trigger(&self) {
+        self.triggered.store(true, Release);
+        for waiter in self.waiting.lock().unwrap().drain(..) {
+            waiter.wake();
+        }
+    }
+
+    pub fn is_triggered(&self) -> bool {
+        self.triggered.load(Acquire)
+    }
+
+    // trigger 될 때 깨울 스레드를 등록한다.
+    // is_triggered 를 확인하기 전에 등록해야 그 사이의 trigger 를 놓치지 않는다.
+    pub fn register(&self, thread: Thread) {
+        self.add(Waiter::Thread(thread));
+    }
+
+    // thread::park 대신 Parker 로 기다리는 쪽을 위한 register
+    pub fn register_unparker(&self, unparker: Unparker) {
+        self.add(Waiter::Unparker(unparker));
+    }
+
+    // trigger 가 이미 목록을 비운 뒤라면 넣어 봐야 아무도 꺼내지 않으므로, 넣지 않고 바로 깨운다.
+    // 잠금을 잡은 채로 확인하므로, 여기서 false 를 봤다면 trigger 의 drain 은 아직 일어나지 않았다.
+    fn add(&self, waiter: Waiter) {
+        let mut waiting = self.waiting.lock().unwrap();
+        if self.is_triggered() {
+            drop(waiting);
+            waiter.wake();
+        } else {
+            waiting.push(waiter);
+        }
+    }
+
+    // 더는 기다리지 않는 쪽이 등록을 지운다. 같은 token 을 오래 쓸 때 목록이 계속 커지지 않게 한다.
+    pub fn deregister_unparker(&self, unparker: &Unparker) {
+        self.waiting.lock().unwrap().retain(|waiter| match waiter {
+            Waiter::Unparker(u) => !u.ptr_eq(unparker),
+            Waiter::Thread(_) => true,
+        });
+    }
+}
+
+impl Default for ShutdownToken {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use crate::ch2::shutdown::ShutdownToken;
+    use crate::ch5::channel::ch5_6;
+    use crate::ch5::channel::parker::Parker;
+
+    #[test]
+    fn worker_stops_promptly() {
+        let token = ShutdownToken::new();
+        thread::scope(|s| {
+            let worker = s.spawn(|| {
+                token.register(thread::current());
+                while !token.is_triggered() {
+                    thread::park();
+                }
+            });
+
+            thread::sleep(Duration::from_millis(10));
+            let start = Instant::now();
+            token.trigger();
+            worker.join().unwrap();
+            assert!(start.elapsed() < Duration::from_secs(1));
+        });
+    }
+
+    #[test]
+    fn register_after_trigger() {
+        let token = ShutdownToken::new();
+        token.trigger();
+        token.register(thread::current());
+        assert!(token.is_triggered());
+        // 이미 trigger 되었으므로 목록에 남지 않고 바로 깨운다.
+        assert!(token.waiting.lock().unwrap().is_empty());
+
+        let parker = Parker::new();
+        token.register_unparker(parker.unparker());
+        assert!(token.waiting.lock().unwrap().is_empty());
+        assert!(parker.park_timeout(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn cancellable_receives_do_not_pile_up() {
+        let token = ShutdownToken::new();
+        for i in 0..100 {
+            let mut channel = ch5_6::Channel::new();
+            let (sender, receiver) = channel.split();
+            sender.send(i);
+            assert_eq!(receiver.receive_cancellable(&token), Ok(i));
+        }
+        assert!(token.waiting.lock().unwrap().is_empty());
+    }
+}
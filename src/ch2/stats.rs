@@ -0,0 +1,123 @@
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+
+// 항목 처리 시간의 개수, 최솟값, 최댓값.
+// min/max 를 먼저 갱신한 뒤 count 를 Release 로 올리고, 읽는 쪽은 count 를 Acquire 로 읽는다.
+// 그래서 count > 0 을 본 스레드는 적어도 그 기록의 min/max 를 보고, 초깃값(u64::MAX, 0)을 값으로 내놓지 않는다.
+pub struct ConcurrentStats {
+    count: AtomicU64,
+    min: AtomicU64,
+    max: AtomicU64,
+}
+
+impl ConcurrentStats {
+    pub const fn new() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+            min: AtomicU64::new(u64::MAX),
+            max: AtomicU64::new(0),
+        }
+    }
+
+    pub fn record(&self, dur_ms: u64) {
+        // 더 작을(클) 때만 바꾸고, 아니면 None 을 돌려줘서 쓰기 없이 끝낸다.
+        let _ = self.min.fetch_update(Relaxed, Relaxed, |min| (dur_ms < min).then_some(dur_ms));
+        let _ = self.max.fetch_update(Relaxed, Relaxed, |max| (dur_ms > max).then_some(dur_ms));
+        self.count.fetch_add(1, Release);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Acquire)
+    }
+
+    // 아직 기록된 값이 없으면 None
+    pub fn min(&self) -> Option<u64> {
+        self.snapshot().min
+    }
+
+    pub fn max(&self) -> Option<u64> {
+        self.snapshot().max
+    }
+
+    // count 를 한 번만 읽어서 세 값을 함께 돌려준다.
+    // count, min, max 를 따로 부르면 그 사이에 기록이 들어와 count 는 0 인데 min 은 Some 일 수 있다.
+    pub fn snapshot(&self) -> StatsSnapshot {
+        let count = self.count();
+        let recorded = count > 0;
+        StatsSnapshot {
+            count,
+            min: Some(self.min.load(Relaxed)).filter(|_| recorded),
+            max: Some(self.max.load(Relaxed)).filter(|_| recorded),
+        }
+    }
+}
+
+// count 가 0 이면 min, max 는 None 이고, 0 이 아니면 둘 다 Some 이다.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatsSnapshot {
+    pub count: u64,
+    pub min: Option<u64>,
+    pub max: Option<u64>,
+}
+
+impl Default for ConcurrentStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use crate::ch2::stats::{ConcurrentStats, StatsSnapshot};
+
+    #[test]
+    fn empty() {
+        let stats = ConcurrentStats::new();
+        assert_eq!(stats.count(), 0);
+        assert_eq!(stats.min(), None);
+        assert_eq!(stats.max(), None);
+        assert_eq!(stats.snapshot(), StatsSnapshot { count: 0, min: None, max: None });
+    }
+
+    #[test]
+    fn record_from_many_threads() {
+        let stats = ConcurrentStats::new();
+        thread::scope(|s| {
+            for t in 0..4 {
+                let stats = &stats;
+                s.spawn(move || {
+                    for i in 0..250 {
+                        stats.record(10 + t * 250 + i);
+                    }
+                });
+            }
+        });
+        assert_eq!(stats.count(), 1000);
+        assert_eq!(stats.min(), Some(10));
+        assert_eq!(stats.max(), Some(1009));
+    }
+
+    // count 가 0 이 아니면 min/max 는 이미 실제 기록값이어야 한다.
+    #[test]
+    fn count_never_runs_ahead_of_min_max() {
+        let stats = ConcurrentStats::new();
+        thread::scope(|s| {
+            s.spawn(|| {
+                for i in 1..=1000 {
+                    stats.record(i);
+                }
+            });
+            while stats.count() < 1000 {
+                let snapshot = stats.snapshot();
+                if snapshot.count > 0 {
+                    assert!(snapshot.min.is_some_and(|min| min != u64::MAX));
+                    assert!(snapshot.max.is_some_and(|max| max != 0));
+                } else {
+                    assert_eq!(snapshot.min, None);
+                    assert_eq!(snapshot.max, None);
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,77 @@
+// fence 로 하는 메시지 전달
+// producer 는 여러 DATA 를 Relaxed 로 쓰고, Release fence 를 한 번 둔 뒤 READY 를 Relaxed 로 켠다.
+// consumer 는 READY 를 Relaxed 로 보고, Acquire fence 를 한 번 둔 뒤 DATA 를 Relaxed 로 읽는다.
+// READY 의 store 가 fence 뒤에, load 가 fence 앞에 있으므로 두 fence 사이에 happens-before 관계가 생긴다.
+// READY 를 Release/Acquire 로 쓰는 것과 같은 보장이고, 순서가 필요한 지점마다가 아니라 fence 한 쌍으로 모든 DATA 를 지킨다.
+
+use std::sync::atomic::{fence, AtomicBool, AtomicU64};
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+use std::thread;
+
+pub const WORDS: usize = 8;
+
+pub fn fence_version() -> [u64; WORDS] {
+    let data: [AtomicU64; WORDS] = Default::default();
+    let ready = AtomicBool::new(false);
+
+    thread::scope(|s| {
+        s.spawn(|| {
+            for (i, d) in data.iter().enumerate() {
+                d.store(i as u64 + 1, Relaxed);
+            }
+            fence(Release);
+            ready.store(true, Relaxed);
+        });
+
+        while !ready.load(Relaxed) {
+            thread::yield_now();
+        }
+        fence(Acquire);
+        data.each_ref().map(|d| d.load(Relaxed))
+    })
+}
+
+// 여러 플래그를 기다리지만, 실제로 켜진 것을 본 뒤에만 Acquire fence 를 한 번 친다.
+// 플래그마다 Acquire load 를 하는 것보다 싸고, 켜지지 않은 플래그에는 순서 비용을 치르지 않는다.
+pub fn first_ready(flags: &[AtomicBool]) -> Option<usize> {
+    let i = flags.iter().position(|f| f.load(Relaxed))?;
+    fence(Acquire);
+    Some(i)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicU64};
+    use std::sync::atomic::Ordering::{Relaxed, Release};
+    use std::thread;
+    use crate::ch3::fences::{fence_version, first_ready, WORDS};
+
+    #[test]
+    fn all_words_visible() {
+        let expected: [u64; WORDS] = std::array::from_fn(|i| i as u64 + 1);
+        for _ in 0..10_000 {
+            assert_eq!(fence_version(), expected);
+        }
+    }
+
+    #[test]
+    fn first_ready_sees_data() {
+        let data = AtomicU64::new(0);
+        let flags: [AtomicBool; 4] = Default::default();
+        assert_eq!(first_ready(&flags), None);
+        thread::scope(|s| {
+            s.spawn(|| {
+                data.store(42, Relaxed);
+                flags[2].store(true, Release);
+            });
+            let i = loop {
+                if let Some(i) = first_ready(&flags) {
+                    break i;
+                }
+                thread::yield_now();
+            };
+            assert_eq!(i, 2);
+            assert_eq!(data.load(Relaxed), 42);
+        });
+    }
+}
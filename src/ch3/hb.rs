@@ -0,0 +1,81 @@
+// 테스트에서 happens-before 관계를 확인하기 위한 논리 시계.
+// 보호받는 쪽의 store 마다 on_store 로 번호를 매겨 Relaxed 로 적어 두고, 읽는 쪽은 on_load 로 보이는 번호를 읽는다.
+// 번호는 Relaxed 로만 오가므로, 읽는 쪽이 번호 n 이상을 본다는 것은 검사하려는 동기화(Release/Acquire 등)가
+// n 번 store 를 읽는 쪽에게 보이게 했다는 뜻이다. 동기화가 없으면 약한 메모리 모델에서 더 작은 번호를 볼 수 있다.
+
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::Relaxed;
+
+pub struct ShadowClock {
+    ticks: AtomicU64,
+    last_store: AtomicU64,
+}
+
+impl ShadowClock {
+    pub const fn new() -> Self {
+        Self {
+            ticks: AtomicU64::new(0),
+            last_store: AtomicU64::new(0),
+        }
+    }
+
+    // 보호받는 store 바로 옆에서 호출한다. 이 store 의 번호를 돌려주며, 번호는 1 부터 늘어난다.
+    pub fn on_store(&self) -> u64 {
+        let stamp = self.ticks.fetch_add(1, Relaxed) + 1;
+        self.last_store.store(stamp, Relaxed);
+        stamp
+    }
+
+    // 지금 이 스레드에게 보이는 마지막 store 의 번호. 아무것도 안 보이면 0.
+    pub fn on_load(&self) -> u64 {
+        self.last_store.load(Relaxed)
+    }
+
+    // stamp 번 store 가 이 스레드의 load 보다 먼저 일어났는지 확인한다.
+    #[track_caller]
+    pub fn assert_happens_before(&self, stamp: u64) {
+        let seen = self.on_load();
+        assert!(seen >= stamp, "load observed store {seen}, expected at least {stamp}");
+    }
+}
+
+impl Default for ShadowClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU64;
+    use std::sync::atomic::Ordering::{Acquire, Release};
+    use std::thread;
+    use crate::ch3::hb::ShadowClock;
+
+    #[test]
+    fn release_acquire_orders_stamp() {
+        for _ in 0..1000 {
+            let clock = ShadowClock::new();
+            let flag = AtomicU64::new(0);
+            thread::scope(|s| {
+                s.spawn(|| {
+                    let stamp = clock.on_store();
+                    flag.store(stamp, Release);
+                });
+                let stamp = loop {
+                    match flag.load(Acquire) {
+                        0 => thread::yield_now(),
+                        stamp => break stamp,
+                    }
+                };
+                clock.assert_happens_before(stamp);
+            });
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "expected at least 1")]
+    fn missing_store_detected() {
+        ShadowClock::new().assert_happens_before(1);
+    }
+}
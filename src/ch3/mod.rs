@@ -0,0 +1,6 @@
+pub mod fences;
+#[cfg(test)]
+pub mod hb;
+pub mod ordering;
+pub mod seqcst;
+pub mod traced;
\ No newline at end of file
@@ -0,0 +1,63 @@
+// 메시지 전달
+// producer 는 DATA 를 쓴 뒤 READY 를 true 로 만들고, consumer 는 READY 를 본 뒤 DATA 를 읽는다.
+// Relaxed 로는 READY 를 본 consumer 가 DATA 의 새 값을 본다는 보장이 없다.
+// (x86 처럼 강한 메모리 모델을 가진 CPU 에서는 실제로 잘 드러나지 않지만, ARM 같은 곳에서는 드러날 수 있다.)
+// Release/Acquire 로는 READY 의 store 와 load 사이에 happens-before 관계가 생기므로 항상 새 값을 본다.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+use std::thread;
+
+const MESSAGE: u64 = 123;
+
+pub fn relaxed_version() -> u64 {
+    static DATA: AtomicU64 = AtomicU64::new(0);
+    static READY: AtomicBool = AtomicBool::new(false);
+
+    message_passing(&DATA, &READY, Relaxed, Relaxed)
+}
+
+pub fn release_acquire_version() -> u64 {
+    static DATA: AtomicU64 = AtomicU64::new(0);
+    static READY: AtomicBool = AtomicBool::new(false);
+
+    message_passing(&DATA, &READY, Release, Acquire)
+}
+
+fn message_passing(data: &AtomicU64, ready: &AtomicBool, store: Ordering, load: Ordering) -> u64 {
+    data.store(0, Relaxed);
+    ready.store(false, Relaxed);
+
+    thread::scope(|s| {
+        s.spawn(|| {
+            data.store(MESSAGE, Relaxed);
+            ready.store(true, store);
+        });
+
+        while !ready.load(load) {
+            thread::yield_now();
+        }
+        data.load(Relaxed)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ch3::ordering::{relaxed_version, release_acquire_version, MESSAGE};
+
+    const ITERATIONS: usize = 10_000;
+
+    #[test]
+    fn release_acquire_never_stale() {
+        for _ in 0..ITERATIONS {
+            assert_eq!(release_acquire_version(), MESSAGE);
+        }
+    }
+
+    // Relaxed 는 오래된 값을 읽을 수도 있으므로 실패로 취급하지 않고 횟수만 보고한다.
+    #[test]
+    fn relaxed_may_be_stale() {
+        let stale = (0..ITERATIONS).filter(|_| relaxed_version() != MESSAGE).count();
+        println!("relaxed: {stale}/{ITERATIONS} stale reads");
+    }
+}
@@ -0,0 +1,76 @@
+// store buffering
+// 두 스레드가 각자 자기 플래그를 켠 뒤 상대의 플래그를 읽는다.
+// SeqCst 는 모든 SeqCst 연산에 하나의 전체 순서를 주므로, 먼저 켠 쪽의 store 를 나중 쪽의 load 가 반드시 본다.
+// 따라서 둘 다 false 를 읽을 수 없다.
+// Release/Acquire 는 store 와 그것을 읽은 load 사이의 관계만 만들 뿐이라, 서로 상대의 store 를 못 보고 둘 다 false 를 읽을 수 있다.
+// (x86 에서도 store buffer 때문에 실제로 일어난다. 단, 코어가 하나뿐이면 드러나지 않는다.)
+
+use std::sync::Barrier;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::Ordering::Relaxed;
+use std::thread;
+
+// 실험을 iterations 번 반복해서 두 스레드가 모두 false 를 읽은 횟수를 돌려준다.
+// 매번 스레드를 새로 만들면 spawn 비용 때문에 두 스레드가 겹쳐 돌 일이 거의 없으므로,
+// 두 스레드를 한 번만 띄우고 barrier 로 매 반복의 시작과 끝을 맞춘다.
+// barrier 가 happens-before 를 만들어 주므로 플래그 초기화와 결과 읽기는 Relaxed 로 충분하다.
+pub fn store_buffering_demo(store: Ordering, load: Ordering, iterations: usize) -> usize {
+    let a = AtomicBool::new(false);
+    let b = AtomicBool::new(false);
+    let saw_b = AtomicBool::new(false);
+    let saw_a = AtomicBool::new(false);
+    let start = Barrier::new(3);
+    let end = Barrier::new(3);
+    thread::scope(|s| {
+        s.spawn(|| {
+            for _ in 0..iterations {
+                start.wait();
+                a.store(true, store);
+                saw_b.store(b.load(load), Relaxed);
+                end.wait();
+            }
+        });
+        s.spawn(|| {
+            for _ in 0..iterations {
+                start.wait();
+                b.store(true, store);
+                saw_a.store(a.load(load), Relaxed);
+                end.wait();
+            }
+        });
+        (0..iterations)
+            .filter(|_| {
+                a.store(false, Relaxed);
+                b.store(false, Relaxed);
+                start.wait();
+                end.wait();
+                !saw_b.load(Relaxed) && !saw_a.load(Relaxed)
+            })
+            .count()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::Ordering::{Acquire, Relaxed, Release, SeqCst};
+    use crate::ch3::seqcst::store_buffering_demo;
+
+    const ITERATIONS: usize = 10_000;
+
+    #[test]
+    fn seqcst_never_both_false() {
+        assert_eq!(store_buffering_demo(SeqCst, SeqCst, ITERATIONS), 0);
+    }
+
+    // Release/Acquire 와 Relaxed 는 둘 다 false 를 허용하므로 실패로 취급하지 않고 횟수만 보고한다.
+    // 확인하는 것이 없는 데모라 기본으로는 돌리지 않는다.
+    // `cargo test --release weaker_orderings -- --ignored --nocapture` 로 실행한다.
+    #[test]
+    #[ignore]
+    fn weaker_orderings_may_both_be_false() {
+        let release_acquire = store_buffering_demo(Release, Acquire, ITERATIONS);
+        let relaxed = store_buffering_demo(Relaxed, Relaxed, ITERATIONS);
+        println!("release/acquire: {release_acquire}/{ITERATIONS} both false");
+        println!("relaxed:         {relaxed}/{ITERATIONS} both false");
+    }
+}
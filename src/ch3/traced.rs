@@ -0,0 +1,100 @@
+// 어떤 메모리 순서로 연산했는지 기록하는 AtomicU32.
+// `--features trace` 로 빌드하면 이 스레드에서 한 연산이 스레드마다 따로 쌓이고, take_trace 로 꺼내 볼 수 있다.
+// 기능을 끄면 기록하지 않고 AtomicU32 에 그대로 넘기기만 한다.
+
+#[cfg(feature = "trace")]
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Load(Ordering),
+    Store(Ordering),
+    // (성공했을 때의 순서, 실패했을 때의 순서)
+    CompareExchange(Ordering, Ordering),
+}
+
+#[cfg(feature = "trace")]
+thread_local! {
+    static TRACE: RefCell<Vec<Op>> = const { RefCell::new(Vec::new()) };
+}
+
+// 지금까지 이 스레드에 쌓인 기록을 꺼내고 비운다.
+#[cfg(feature = "trace")]
+pub fn take_trace() -> Vec<Op> {
+    TRACE.with(|t| t.take())
+}
+
+#[cfg(feature = "trace")]
+fn record(op: Op) {
+    TRACE.with(|t| t.borrow_mut().push(op));
+}
+
+#[cfg(not(feature = "trace"))]
+#[inline(always)]
+fn record(_op: Op) {}
+
+pub struct TracedAtomicU32 {
+    inner: AtomicU32,
+}
+
+impl TracedAtomicU32 {
+    pub const fn new(value: u32) -> Self {
+        Self { inner: AtomicU32::new(value) }
+    }
+
+    pub fn load(&self, order: Ordering) -> u32 {
+        record(Op::Load(order));
+        self.inner.load(order)
+    }
+
+    pub fn store(&self, value: u32, order: Ordering) {
+        record(Op::Store(order));
+        self.inner.store(value, order);
+    }
+
+    pub fn compare_exchange(&self, current: u32, new: u32, success: Ordering, failure: Ordering) -> Result<u32, u32> {
+        record(Op::CompareExchange(success, failure));
+        self.inner.compare_exchange(current, new, success, failure)
+    }
+}
+
+#[cfg(all(test, feature = "trace"))]
+mod tests {
+    use std::sync::atomic::Ordering::{Acquire, Relaxed, Release, SeqCst};
+    use std::thread;
+    use crate::ch3::traced::{take_trace, Op, TracedAtomicU32};
+
+    #[test]
+    fn records_orderings_in_order() {
+        take_trace();
+        let a = TracedAtomicU32::new(0);
+        a.store(1, Release);
+        assert_eq!(a.load(Acquire), 1);
+        assert_eq!(a.compare_exchange(1, 2, SeqCst, Relaxed), Ok(1));
+        assert_eq!(a.compare_exchange(1, 3, Acquire, Relaxed), Err(2));
+        assert_eq!(
+            take_trace(),
+            [
+                Op::Store(Release),
+                Op::Load(Acquire),
+                Op::CompareExchange(SeqCst, Relaxed),
+                Op::CompareExchange(Acquire, Relaxed),
+            ]
+        );
+        assert_eq!(take_trace(), []);
+    }
+
+    #[test]
+    fn other_threads_have_their_own_trace() {
+        take_trace();
+        let a = TracedAtomicU32::new(0);
+        thread::scope(|s| {
+            s.spawn(|| {
+                a.store(1, Relaxed);
+                assert_eq!(take_trace(), [Op::Store(Relaxed)]);
+            });
+        });
+        assert_eq!(take_trace(), []);
+    }
+}
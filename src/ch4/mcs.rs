@@ -0,0 +1,134 @@
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicPtr};
+use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed, Release};
+use crate::ch2::backoff::Backoff;
+
+// MCS 큐 잠금
+// 기다리는 스레드마다 자기 노드를 큐에 연결하고, 모두 같은 플래그가 아닌 자기 노드의 locked 만 바라본다.
+// SpinLock 은 기다리는 모든 스레드가 같은 캐시 라인을 읽으므로, unlock 한 번에 그 캐시 라인이 모든 코어에서 무효화된다.
+// MCS 에서는 unlock 이 다음 스레드의 노드 하나에만 쓰므로, 그 스레드의 캐시 라인만 바뀐다.
+pub struct McsLock<T> {
+    // 큐의 마지막 노드. 비어 있으면 null 이고 잠겨 있지 않다.
+    tail: AtomicPtr<McsNode>,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T> Sync for McsLock<T> where T: Send {}
+
+pub struct McsNode {
+    next: AtomicPtr<McsNode>,
+    locked: AtomicBool,
+}
+
+pub struct Guard<'a, T> {
+    lock: &'a McsLock<T>,
+    node: &'a McsNode,
+}
+
+unsafe impl<T> Sync for Guard<'_, T> where T: Sync {}
+
+impl McsNode {
+    pub const fn new() -> Self {
+        Self {
+            next: AtomicPtr::new(ptr::null_mut()),
+            locked: AtomicBool::new(false),
+        }
+    }
+}
+
+impl Default for McsNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> McsLock<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            tail: AtomicPtr::new(ptr::null_mut()),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    // node 는 guard 가 살아 있는 동안 빌려지므로, 다른 스레드가 가리키는 동안 옮겨지거나 해제되지 않는다.
+    pub fn lock<'a>(&'a self, node: &'a mut McsNode) -> Guard<'a, T> {
+        node.next = AtomicPtr::new(ptr::null_mut());
+        node.locked = AtomicBool::new(true);
+        let node = &*node;
+        let node_ptr = node as *const McsNode as *mut McsNode;
+
+        // AcqRel: 앞 노드의 초기화를 보고, 우리 노드의 초기화를 뒤에 오는 스레드에게 보인다.
+        let prev = self.tail.swap(node_ptr, AcqRel);
+        if !prev.is_null() {
+            // 앞 스레드에게 우리 노드를 알리고, 앞 스레드가 넘겨줄 때까지 우리 노드만 바라본다.
+            unsafe { (*prev).next.store(node_ptr, Release) };
+            let backoff = Backoff::new();
+            while node.locked.load(Acquire) {
+                backoff.snooze();
+            }
+        }
+        Guard { lock: self, node }
+    }
+}
+
+impl<T> Deref for Guard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T> DerefMut for Guard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T> Drop for Guard<'_, T> {
+    fn drop(&mut self) {
+        let node_ptr = self.node as *const McsNode as *mut McsNode;
+        let mut next = self.node.next.load(Acquire);
+        if next.is_null() {
+            // 뒤에 아무도 없으면 큐를 비운다.
+            if self.lock.tail.compare_exchange(node_ptr, ptr::null_mut(), Release, Relaxed).is_ok() {
+                return;
+            }
+            // tail 은 바뀌었지만 아직 next 를 연결하지 못한 스레드가 있다. 연결될 때까지 기다린다.
+            let backoff = Backoff::new();
+            loop {
+                next = self.node.next.load(Acquire);
+                if !next.is_null() {
+                    break;
+                }
+                backoff.snooze();
+            }
+        }
+        // Release: 임계 구역에서 한 일을 다음 스레드에게 넘겨준다.
+        unsafe { (*next).locked.store(false, Release) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use crate::ch4::mcs::{McsLock, McsNode};
+
+    #[test]
+    fn shared_counter() {
+        let counter = McsLock::new(0);
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    let mut node = McsNode::new();
+                    for _ in 0..1000 {
+                        *counter.lock(&mut node) += 1;
+                    }
+                });
+            }
+        });
+        assert_eq!(*counter.lock(&mut McsNode::new()), 8000);
+    }
+}
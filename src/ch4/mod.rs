@@ -0,0 +1,4 @@
+pub mod mcs;
+pub mod rendezvous;
+pub mod spinlock;
+pub mod ticket;
\ No newline at end of file
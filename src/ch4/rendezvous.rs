@@ -0,0 +1,75 @@
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering::{AcqRel, Acquire};
+use crate::ch2::backoff::Backoff;
+
+// 정확히 두 스레드가 만나는 지점. 둘 다 wait 를 호출할 때까지 spin 하며 기다린다.
+// state 하나에 "몇 번째 만남인지" 와 "먼저 온 스레드가 있는지" 를 함께 담는다.
+// 먼저 온 스레드가 state 를 홀수로 만들고, 두 번째 스레드가 다시 짝수로 만들면 한 번의 만남이 끝난다.
+// 짝수가 되면 바로 다음 만남에 쓸 수 있으므로 여러 번 다시 쓸 수 있다.
+// 세 번째 스레드가 같이 쓰면 홀짝이 어긋나므로 두 스레드만 써야 한다.
+pub struct TwoThreadBarrier {
+    state: AtomicU32,
+}
+
+impl TwoThreadBarrier {
+    pub const fn new() -> Self {
+        Self { state: AtomicU32::new(0) }
+    }
+
+    pub fn wait(&self) {
+        // AcqRel: 먼저 온 스레드가 wait 전에 한 일을 보고, 자기 일도 보여 준다.
+        // u32 가 넘쳐서 0 으로 돌아가도 2^32 는 짝수이므로 홀짝은 그대로다.
+        let old = self.state.fetch_add(1, AcqRel);
+        if old % 2 == 1 {
+            // 두 번째로 왔다. 방금 state 를 짝수로 만들어서 먼저 온 스레드를 풀어 주었다.
+            return;
+        }
+        // 먼저 왔다. 상대가 state 를 바꿀 때까지 기다린다.
+        // 상대가 곧바로 다음 만남에 들어가서 state 가 old + 2 를 지나 있을 수도 있으므로,
+        // old + 2 가 되기를 기다리지 않고 old + 1 에서 바뀌기만 하면 나간다.
+        let backoff = Backoff::new();
+        while self.state.load(Acquire) == old.wrapping_add(1) {
+            backoff.snooze();
+        }
+    }
+}
+
+impl Default for TwoThreadBarrier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use crate::ch4::rendezvous::TwoThreadBarrier;
+
+    #[test]
+    fn rounds_in_lockstep() {
+        const ROUNDS: usize = 1000;
+        let barrier = TwoThreadBarrier::new();
+        // 각 스레드가 들어선 라운드. Relaxed 로 쓰고 읽으므로 보이는 것은 barrier 덕분이다.
+        let rounds = [AtomicUsize::new(0), AtomicUsize::new(0)];
+
+        thread::scope(|s| {
+            for me in 0..2 {
+                let (barrier, rounds) = (&barrier, &rounds);
+                s.spawn(move || {
+                    let other = &rounds[1 - me];
+                    for round in 1..=ROUNDS {
+                        rounds[me].store(round, Relaxed);
+                        barrier.wait();
+                        // 상대도 이 라운드에 들어섰고, 다음 wait 를 지나지 않았으므로 한 라운드 넘게 앞설 수 없다.
+                        let seen = other.load(Relaxed);
+                        assert!(seen == round || seen == round + 1, "round {round}: other at {seen}");
+                    }
+                });
+            }
+        });
+        assert_eq!(rounds[0].load(Relaxed), ROUNDS);
+        assert_eq!(rounds[1].load(Relaxed), ROUNDS);
+    }
+}
@@ -0,0 +1,236 @@
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::AtomicBool;
+#[cfg(feature = "spin-stats")]
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+use crate::ch2::backoff::Backoff;
+use crate::ch7::cache::CachePadded;
+
+pub struct SpinLock<T> {
+    // 잠금 플래그가 이웃한 데이터(다른 SpinLock 포함)와 캐시 라인을 나눠 쓰지 않게 한다.
+    locked: CachePadded<AtomicBool>,
+    // lock 의 안쪽 loop 를 돈 횟수. 기다리는 스레드들이 돌 때마다 쓰는 값이라,
+    // locked 를 load 하며 도는 캐시 라인을 흔들지 않도록 따로 패딩한다.
+    #[cfg(feature = "spin-stats")]
+    spins: CachePadded<AtomicU64>,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T> Sync for SpinLock<T> where T: Send {}
+
+pub struct Guard<'a, T> {
+    lock: &'a SpinLock<T>,
+}
+
+unsafe impl<T> Sync for Guard<'_, T> where T: Sync {}
+
+impl<T> SpinLock<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            locked: CachePadded::new(AtomicBool::new(false)),
+            #[cfg(feature = "spin-stats")]
+            spins: CachePadded::new(AtomicU64::new(0)),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub fn lock(&self) -> Guard<'_, T> {
+        let backoff = Backoff::new();
+        while self.locked.compare_exchange_weak(false, true, Acquire, Relaxed).is_err() {
+            // 잠겨 있는 동안에는 load 만 해서 cache line 에 쓰기 경합을 만들지 않는다.
+            while self.locked.load(Relaxed) {
+                #[cfg(feature = "spin-stats")]
+                self.spins.fetch_add(1, Relaxed);
+                backoff.snooze();
+            }
+        }
+        Guard { lock: self }
+    }
+
+    // `--features spin-stats` 로 빌드했을 때, 지금까지 lock 이 잠금을 기다리며 돈 횟수의 합.
+    // 안쪽 loop 는 잠겨 있는 것을 봤을 때만 돌기 때문에, 경쟁이 없으면 0 에 가깝게 남는다.
+    // (try_lock, try_lock_for 는 세지 않는다.)
+    #[cfg(feature = "spin-stats")]
+    pub fn spin_count(&self) -> u64 {
+        self.spins.load(Relaxed)
+    }
+
+    // 한 번만 시도하고, 실패하면 기다리지 않고 None 을 돌려준다.
+    pub fn try_lock(&self) -> Option<Guard<'_, T>> {
+        self.locked
+            .compare_exchange(false, true, Acquire, Relaxed)
+            .ok()
+            .map(|_| Guard { lock: self })
+    }
+
+    // 최대 spins 번까지만 기다려 보고 포기한다.
+    pub fn try_lock_for(&self, spins: u32) -> Option<Guard<'_, T>> {
+        for _ in 0..spins {
+            if let Some(guard) = self.try_lock() {
+                return Some(guard);
+            }
+            std::hint::spin_loop();
+        }
+        self.try_lock()
+    }
+}
+
+impl<T> Deref for Guard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T> DerefMut for Guard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T> Drop for Guard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.locked.store(false, Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use crate::ch4::spinlock::SpinLock;
+
+    #[test]
+    fn spinlock_test() {
+        let counter = SpinLock::new(0);
+        thread::scope(|s| {
+            for _ in 0..10 {
+                s.spawn(|| {
+                    for _ in 0..1000 {
+                        *counter.lock() += 1;
+                    }
+                });
+            }
+        });
+        assert_eq!(*counter.lock(), 10000);
+    }
+
+    #[test]
+    fn try_lock_test() {
+        let lock = SpinLock::new(0);
+        let guard = lock.lock();
+        thread::scope(|s| {
+            s.spawn(|| {
+                assert!(lock.try_lock().is_none());
+                assert!(lock.try_lock_for(100).is_none());
+            });
+        });
+        drop(guard);
+        thread::scope(|s| {
+            s.spawn(|| {
+                assert!(lock.try_lock().is_some());
+            });
+        });
+    }
+
+    // Guard 의 Drop 은 unwind 중에도 실행되므로 panic 해도 잠금이 풀린다.
+    #[test]
+    fn panic_while_locked_releases() {
+        use std::panic::{catch_unwind, AssertUnwindSafe};
+
+        let lock = SpinLock::new(0);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let mut guard = lock.lock();
+            *guard += 1;
+            panic!("panic while holding the lock");
+        }));
+        assert!(result.is_err());
+        assert_eq!(*lock.try_lock().unwrap(), 1);
+    }
+
+    #[cfg(feature = "spin-stats")]
+    #[test]
+    fn spin_count_under_contention() {
+        use std::time::Duration;
+
+        let lock = SpinLock::new(0);
+        for _ in 0..100 {
+            *lock.lock() += 1;
+        }
+        // 혼자 잠그면 기다릴 일이 없다.
+        assert_eq!(lock.spin_count(), 0);
+
+        let guard = lock.lock();
+        thread::scope(|s| {
+            s.spawn(|| *lock.lock() += 1);
+            thread::sleep(Duration::from_millis(10));
+            drop(guard);
+        });
+        assert!(lock.spin_count() > 0);
+        assert_eq!(*lock.lock(), 101);
+    }
+
+    // `cargo test --release padded_vs_unpadded -- --ignored --nocapture` 로 실행한다.
+    // 스레드마다 자기 잠금만 쓰므로 경쟁은 false sharing 에서만 생긴다.
+    #[test]
+    #[ignore]
+    fn padded_vs_unpadded() {
+        use std::sync::atomic::AtomicBool;
+        use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+        use std::time::Instant;
+
+        const THREADS: usize = 4;
+        const ITERATIONS: usize = 1_000_000;
+
+        // 패딩이 없는 것 말고는 SpinLock 과 같은 잠금
+        struct Unpadded {
+            locked: AtomicBool,
+        }
+
+        let unpadded: [Unpadded; THREADS] = std::array::from_fn(|_| Unpadded { locked: AtomicBool::new(false) });
+        let start = Instant::now();
+        thread::scope(|s| {
+            for lock in &unpadded {
+                s.spawn(move || {
+                    for _ in 0..ITERATIONS {
+                        while lock.locked.compare_exchange_weak(false, true, Acquire, Relaxed).is_err() {
+                            std::hint::spin_loop();
+                        }
+                        lock.locked.store(false, Release);
+                    }
+                });
+            }
+        });
+        println!("unpadded: {:?}", start.elapsed());
+
+        let padded: [SpinLock<()>; THREADS] = std::array::from_fn(|_| SpinLock::new(()));
+        let start = Instant::now();
+        thread::scope(|s| {
+            for lock in &padded {
+                s.spawn(move || {
+                    for _ in 0..ITERATIONS {
+                        drop(lock.lock());
+                    }
+                });
+            }
+        });
+        println!("padded:   {:?}", start.elapsed());
+    }
+
+    #[test]
+    fn spinlock_oversubscribed() {
+        let threads = thread::available_parallelism().map_or(4, |n| n.get()) * 4;
+        let counter = SpinLock::new(0);
+        thread::scope(|s| {
+            for _ in 0..threads {
+                s.spawn(|| {
+                    for _ in 0..1000 {
+                        *counter.lock() += 1;
+                    }
+                });
+            }
+        });
+        assert_eq!(*counter.lock(), threads * 1000);
+    }
+}
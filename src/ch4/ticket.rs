@@ -0,0 +1,97 @@
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+use crate::ch2::backoff::Backoff;
+
+// 번호표 잠금: 먼저 번호표를 뽑은 스레드가 먼저 들어간다.
+// SpinLock 은 풀렸을 때 누가 잡을지 정해져 있지 않아서 어떤 스레드는 오래 굶을 수 있지만,
+// TicketLock 은 FIFO 순서를 보장한다.
+pub struct TicketLock<T> {
+    next_ticket: AtomicU32,
+    now_serving: AtomicU32,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T> Sync for TicketLock<T> where T: Send {}
+
+pub struct Guard<'a, T> {
+    lock: &'a TicketLock<T>,
+    ticket: u32,
+}
+
+unsafe impl<T> Sync for Guard<'_, T> where T: Sync {}
+
+impl<T> TicketLock<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            next_ticket: AtomicU32::new(0),
+            now_serving: AtomicU32::new(0),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub fn lock(&self) -> Guard<'_, T> {
+        // 번호표 자체는 다른 데이터를 보호하지 않으므로 Relaxed 로 충분하다.
+        let ticket = self.next_ticket.fetch_add(1, Relaxed);
+        let backoff = Backoff::new();
+        // Acquire: 앞 사람의 unlock(Release) 이전에 한 일이 보인다.
+        while self.now_serving.load(Acquire) != ticket {
+            backoff.snooze();
+        }
+        Guard { lock: self, ticket }
+    }
+}
+
+impl<T> Guard<'_, T> {
+    // 이 guard 가 받은 번호표
+    pub fn ticket(&self) -> u32 {
+        self.ticket
+    }
+}
+
+impl<T> Deref for Guard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T> DerefMut for Guard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T> Drop for Guard<'_, T> {
+    fn drop(&mut self) {
+        // 잠금을 쥔 스레드만 now_serving 을 바꾸므로 store 로 충분하다.
+        self.lock.now_serving.store(self.ticket.wrapping_add(1), Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use crate::ch4::ticket::TicketLock;
+
+    #[test]
+    fn served_in_ticket_order() {
+        let lock = TicketLock::new(Vec::new());
+        thread::scope(|s| {
+            for _ in 0..8 {
+                s.spawn(|| {
+                    for _ in 0..100 {
+                        let mut served = lock.lock();
+                        let ticket = served.ticket();
+                        served.push(ticket);
+                    }
+                });
+            }
+        });
+        let served = lock.lock();
+        assert_eq!(served.len(), 800);
+        assert!(served.windows(2).all(|w| w[0] + 1 == w[1]));
+    }
+}
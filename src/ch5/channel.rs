@@ -1,52 +1,488 @@
+pub mod error {
+    use std::error::Error;
+    use std::fmt;
+
+    // 채널들이 공통으로 쓰는 에러 타입.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum ChannelError {
+        // 이미 메시지를 보냈다.
+        AlreadySent,
+        // 아직 받을 메시지가 없다.
+        Empty,
+        // 메시지를 보내지 않은 채로 sender 가 모두 사라졌다.
+        Disconnected,
+        // 메시지를 만들던 중에 sender 가 panic 했다.
+        Poisoned,
+        // 메시지를 받기 전에 정지 신호가 왔다.
+        Cancelled,
+        // 메시지는 이미 받아 갔다.
+        AlreadyReceived,
+        // 정해진 시간 안에 끝나지 못했다.
+        TimedOut,
+    }
+
+    impl fmt::Display for ChannelError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                ChannelError::AlreadySent => write!(f, "can't send more than one message"),
+                ChannelError::Empty => write!(f, "no message available"),
+                ChannelError::Disconnected => write!(f, "channel disconnected"),
+                ChannelError::Poisoned => write!(f, "channel poisoned"),
+                ChannelError::Cancelled => write!(f, "receive cancelled"),
+                ChannelError::AlreadyReceived => write!(f, "message already received"),
+                ChannelError::TimedOut => write!(f, "timed out"),
+            }
+        }
+    }
+
+    impl Error for ChannelError {}
+
+    #[cfg(test)]
+    mod tests {
+        use super::ChannelError;
+
+        #[test]
+        fn display() {
+            assert_eq!(ChannelError::AlreadySent.to_string(), "can't send more than one message");
+            assert_eq!(ChannelError::Empty.to_string(), "no message available");
+            assert_eq!(ChannelError::Disconnected.to_string(), "channel disconnected");
+            assert_eq!(ChannelError::Poisoned.to_string(), "channel poisoned");
+            assert_eq!(ChannelError::Cancelled.to_string(), "receive cancelled");
+            assert_eq!(ChannelError::AlreadyReceived.to_string(), "message already received");
+            assert_eq!(ChannelError::TimedOut.to_string(), "timed out");
+        }
+
+        #[test]
+        fn usable_with_question_mark() {
+            fn receive() -> Result<u32, Box<dyn std::error::Error>> {
+                Err(ChannelError::Empty)?
+            }
+            assert_eq!(receive().unwrap_err().to_string(), "no message available");
+        }
+    }
+}
+
+// `--features metrics` 로 빌드하면 채널들이 send/recv/park/unpark 횟수를 센다.
+// 기능을 끄면 기록 함수가 빈 함수가 되어 완전히 사라진다.
+// 보내는 스레드와 받는 스레드가 다르므로 프로세스 전역 원자 카운터에 세고, snapshot 은 전체 합계를 돌려준다.
+// 횟수만 세면 되므로 Relaxed 로 충분하다.
+pub mod metrics {
+    #[cfg(feature = "metrics")]
+    use std::sync::atomic::AtomicU64;
+    #[cfg(feature = "metrics")]
+    use std::sync::atomic::Ordering::Relaxed;
+
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct Snapshot {
+        pub sends: u64,
+        pub failed_sends: u64,
+        pub receives: u64,
+        pub parks: u64,
+        pub unparks: u64,
+    }
+
+    const SENDS: usize = 0;
+    const FAILED_SENDS: usize = 1;
+    const RECEIVES: usize = 2;
+    const PARKS: usize = 3;
+    const UNPARKS: usize = 4;
+
+    #[cfg(feature = "metrics")]
+    static COUNTERS: [AtomicU64; 5] = [const { AtomicU64::new(0) }; 5];
+
+    #[cfg(feature = "metrics")]
+    pub fn snapshot() -> Snapshot {
+        let get = |i: usize| COUNTERS[i].load(Relaxed);
+        Snapshot {
+            sends: get(SENDS),
+            failed_sends: get(FAILED_SENDS),
+            receives: get(RECEIVES),
+            parks: get(PARKS),
+            unparks: get(UNPARKS),
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    #[inline(always)]
+    fn record(i: usize) {
+        COUNTERS[i].fetch_add(1, Relaxed);
+    }
+
+    #[cfg(not(feature = "metrics"))]
+    #[inline(always)]
+    fn record(_i: usize) {}
+
+    #[inline(always)]
+    pub(crate) fn send(ok: bool) {
+        record(if ok { SENDS } else { FAILED_SENDS });
+    }
+
+    #[inline(always)]
+    pub(crate) fn receive() {
+        record(RECEIVES);
+    }
+
+    #[inline(always)]
+    pub(crate) fn park() {
+        record(PARKS);
+    }
+
+    #[inline(always)]
+    pub(crate) fn unpark() {
+        record(UNPARKS);
+    }
+
+    // 카운터가 전역이고 테스트는 병렬로 돌므로 증가량은 "최소 이만큼" 으로만 확인한다.
+    #[cfg(all(test, feature = "metrics"))]
+    mod tests {
+        use std::thread;
+        use std::time::Duration;
+        use crate::ch5::channel::{bounded, ch5_6};
+        use super::snapshot;
+
+        #[test]
+        fn counts_sends_and_receives() {
+            let before = snapshot();
+            let channel = bounded::Channel::new(2);
+            assert!(channel.send(1).is_ok());
+            assert!(channel.send(2).is_ok());
+            assert!(channel.send(3).is_err());
+            assert_eq!(channel.recv(), Some(1));
+            assert_eq!(channel.recv(), Some(2));
+            assert_eq!(channel.recv(), None);
+
+            let after = snapshot();
+            assert!(after.sends - before.sends >= 2);
+            assert!(after.failed_sends - before.failed_sends >= 1);
+            assert!(after.receives - before.receives >= 2);
+        }
+
+        #[test]
+        fn counts_parks_and_unparks() {
+            let before = snapshot();
+            let mut channel = ch5_6::Channel::new();
+            thread::scope(|s| {
+                let (sender, receiver) = channel.split();
+                s.spawn(move || {
+                    thread::sleep(Duration::from_millis(20));
+                    sender.send(1);
+                });
+                assert_eq!(receiver.receive(), Ok(1));
+            });
+            let after = snapshot();
+            assert!(after.receives - before.receives >= 1);
+            assert!(after.parks > before.parks);
+            assert!(after.unparks > before.unparks);
+        }
+
+        #[test]
+        fn counts_unsent_slice_items_as_failed() {
+            let before = snapshot();
+            let channel = bounded::Channel::new(2);
+            let mut items = vec![1, 2, 3];
+            assert_eq!(channel.send_slice(&mut items), 2);
+            assert_eq!(channel.send_slice(&mut items), 0);
+            let after = snapshot();
+            assert!(after.sends - before.sends >= 2);
+            assert!(after.failed_sends - before.failed_sends >= 2);
+        }
+
+        #[test]
+        fn counts_sends_from_other_threads() {
+            let before = snapshot();
+            let channel = bounded::Channel::new(4);
+            thread::scope(|s| {
+                s.spawn(|| {
+                    assert!(channel.send(1).is_ok());
+                    assert!(channel.send(2).is_ok());
+                });
+            });
+            assert_eq!(channel.recv(), Some(1));
+            assert_eq!(channel.recv(), Some(2));
+            let after = snapshot();
+            assert!(after.sends - before.sends >= 2);
+        }
+    }
+}
+
+pub mod parker {
+    use std::marker::PhantomData;
+    use std::sync::{Arc, Condvar, Mutex};
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering::{Acquire, Release};
+    use std::time::{Duration, Instant};
+
+    // thread::park/unpark 를 대신하는 한 쌍. Thread 핸들 없이 Unparker 만 있으면 깨울 수 있다.
+    // notified 는 unpark 가 남긴 토큰이고, park 는 토큰을 가져갈 때까지 condvar 로 기다린다.
+    // park 보다 unpark 가 먼저 와도 토큰이 남아 있으므로 park 가 바로 돌아온다.
+    struct Inner {
+        notified: AtomicBool,
+        lock: Mutex<()>,
+        condvar: Condvar,
+    }
+
+    // 기다리는 쪽은 하나여야 하므로 Parker 는 Sync 가 아니다.
+    pub struct Parker {
+        inner: Arc<Inner>,
+        _no_sync: PhantomData<*const ()>,
+    }
+
+    unsafe impl Send for Parker {}
+
+    #[derive(Clone)]
+    pub struct Unparker {
+        inner: Arc<Inner>,
+    }
+
+    impl Parker {
+        pub fn new() -> Self {
+            Self {
+                inner: Arc::new(Inner {
+                    notified: AtomicBool::new(false),
+                    lock: Mutex::new(()),
+                    condvar: Condvar::new(),
+                }),
+                _no_sync: PhantomData,
+            }
+        }
+
+        pub fn unparker(&self) -> Unparker {
+            Unparker { inner: self.inner.clone() }
+        }
+
+        pub fn park(&self) {
+            if self.inner.notified.swap(false, Acquire) {
+                return;
+            }
+            let mut guard = self.inner.lock.lock().unwrap();
+            // 잠금을 잡은 채로 확인하므로, unpark 는 확인과 wait 사이에 끼어들 수 없다.
+            while !self.inner.notified.swap(false, Acquire) {
+                guard = self.inner.condvar.wait(guard).unwrap();
+            }
+        }
+
+        // 토큰을 받았으면 true, 시간이 다 되었으면 false
+        pub fn park_timeout(&self, dur: Duration) -> bool {
+            if self.inner.notified.swap(false, Acquire) {
+                return true;
+            }
+            let deadline = Instant::now() + dur;
+            let mut guard = self.inner.lock.lock().unwrap();
+            while !self.inner.notified.swap(false, Acquire) {
+                let now = Instant::now();
+                if now >= deadline {
+                    return false;
+                }
+                guard = self.inner.condvar.wait_timeout(guard, deadline - now).unwrap().0;
+            }
+            true
+        }
+    }
+
+    impl Default for Parker {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl Unparker {
+        pub fn unpark(&self) {
+            self.inner.notified.store(true, Release);
+            // park 가 토큰을 확인한 뒤 wait 에 들어가기 전이라면, 잠금을 잡는 동안 기다렸다가 깨운다.
+            drop(self.inner.lock.lock().unwrap());
+            self.inner.condvar.notify_one();
+        }
+
+        // 같은 Parker 에서 나온 Unparker 인지
+        pub fn ptr_eq(&self, other: &Unparker) -> bool {
+            Arc::ptr_eq(&self.inner, &other.inner)
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::sync::atomic::AtomicBool;
+        use std::sync::atomic::Ordering::Relaxed;
+        use std::thread;
+        use std::time::Duration;
+        use super::Parker;
+
+        #[test]
+        fn unpark_from_other_thread() {
+            let parker = Parker::new();
+            let unparker = parker.unparker();
+            let woken = AtomicBool::new(false);
+            thread::scope(|s| {
+                s.spawn(|| {
+                    thread::sleep(Duration::from_millis(20));
+                    woken.store(true, Relaxed);
+                    unparker.unpark();
+                });
+                parker.park();
+                assert!(woken.load(Relaxed));
+            });
+        }
+
+        #[test]
+        fn unpark_before_park() {
+            let parker = Parker::new();
+            parker.unparker().unpark();
+            parker.park();
+            // 토큰은 한 번만 쓸 수 있다.
+            assert!(!parker.park_timeout(Duration::from_millis(10)));
+        }
+
+        #[test]
+        fn park_in_another_thread() {
+            let parker = Parker::new();
+            let unparker = parker.unparker();
+            let t = thread::spawn(move || parker.park());
+            unparker.unpark();
+            t.join().unwrap();
+        }
+    }
+}
+
+// `RUSTFLAGS="--cfg loom" cargo test --release --test loom` 으로 실행하면
+// std 의 atomic 과 UnsafeCell 대신 loom 의 것을 써서 가능한 모든 실행 순서를 검사한다(tests/loom.rs).
 pub mod unsafe_channel {
-    use std::cell::UnsafeCell;
     use std::mem::MaybeUninit;
-    use std::sync::atomic::AtomicBool;
     use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
     use std::thread;
+    use crate::ch5::channel::error::ChannelError;
+
+    #[cfg(not(loom))]
+    use std::cell::UnsafeCell;
+    #[cfg(not(loom))]
+    use std::sync::atomic::AtomicBool;
+
+    #[cfg(loom)]
+    use loom::cell::UnsafeCell;
+    #[cfg(loom)]
+    use loom::sync::atomic::AtomicBool;
+
+    // std 의 UnsafeCell 도 loom 과 같은 with_mut 으로 접근할 수 있게 한다.
+    #[cfg(not(loom))]
+    pub(super) trait WithMut<T> {
+        fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R;
+    }
+
+    #[cfg(not(loom))]
+    impl<T> WithMut<T> for UnsafeCell<T> {
+        fn with_mut<R>(&self, f: impl FnOnce(*mut T) -> R) -> R {
+            f(self.get())
+        }
+    }
 
     pub struct Channel<T> {
         message: UnsafeCell<MaybeUninit<T>>,
         in_use: AtomicBool,
         ready: AtomicBool,
+        // in_use 를 잡은 뒤 메시지를 쓰기 전에 panic 하면 true 가 된다.
+        // 그렇지 않으면 in_use 만 true 로 남아서 receiver 는 영원히 Empty 만 보게 된다.
+        poisoned: AtomicBool,
+    }
+
+    // send_with 가 끝까지 가지 못하고 unwind 되면 채널을 poisoned 로 표시한다.
+    struct PoisonOnUnwind<'a> {
+        poisoned: &'a AtomicBool,
+    }
+
+    impl Drop for PoisonOnUnwind<'_> {
+        fn drop(&mut self) {
+            self.poisoned.store(true, Release);
+        }
     }
 
     unsafe impl<T> Sync for Channel<T> where T: Send {}
 
+    impl<T> Default for Channel<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
     impl<T> Channel<T> {
+        #[cfg(not(loom))]
         pub const fn new() -> Self {
             Self {
                 message: UnsafeCell::new(MaybeUninit::uninit()),
                 in_use: AtomicBool::new(false),
                 ready: AtomicBool::new(false),
+                poisoned: AtomicBool::new(false),
+            }
+        }
+
+        // loom 의 타입은 const 로 만들 수 없다.
+        #[cfg(loom)]
+        pub fn new() -> Self {
+            Self {
+                message: UnsafeCell::new(MaybeUninit::uninit()),
+                in_use: AtomicBool::new(false),
+                ready: AtomicBool::new(false),
+                poisoned: AtomicBool::new(false),
             }
         }
 
         pub fn send(&self, message: T) {
+            if self.try_send(message).is_err() {
+                panic!("{}", ChannelError::AlreadySent);
+            }
+        }
+
+        // 이미 보낸 적이 있다면 panic 하는 대신 메시지를 돌려준다.
+        pub fn try_send(&self, message: T) -> Result<(), T> {
+            if self.in_use.swap(true, Relaxed) {
+                return Err(message);
+            }
+
+            self.message.with_mut(|m| unsafe { (*m).write(message) });
+            self.ready.store(true, Release);
+            Ok(())
+        }
+
+        // 채널을 차지한 뒤에 f 로 메시지를 만든다. f 가 panic 하면 채널은 poisoned 가 된다.
+        pub fn send_with(&self, f: impl FnOnce() -> T) {
             if self.in_use.swap(true, Relaxed) {
-                panic!("can't send more than one message!");
+                panic!("{}", ChannelError::AlreadySent);
             }
 
-            unsafe { (*self.message.get()).write(message) };
+            let guard = PoisonOnUnwind { poisoned: &self.poisoned };
+            let message = f();
+            std::mem::forget(guard);
+
+            self.message.with_mut(|m| unsafe { (*m).write(message) });
             self.ready.store(true, Release);
         }
 
+        pub fn is_poisoned(&self) -> bool {
+            self.poisoned.load(Acquire)
+        }
+
         pub fn is_ready(&self) -> bool {
             self.ready.load(Relaxed)
         }
 
         pub fn receive(&self) -> T {
+            self.try_receive().unwrap_or_else(|e| panic!("{e}"))
+        }
+
+        pub fn try_receive(&self) -> Result<T, ChannelError> {
             if !self.ready.swap(false, Acquire) {
-                panic!("no message available!");
+                if self.is_poisoned() {
+                    return Err(ChannelError::Poisoned);
+                }
+                return Err(ChannelError::Empty);
             }
-            unsafe { (*self.message.get()).assume_init_read() }
+            Ok(self.message.with_mut(|m| unsafe { (*m).assume_init_read() }))
         }
     }
 
     impl<T> Drop for Channel<T> {
         fn drop(&mut self) {
-            if *self.ready.get_mut() {
-                unsafe { self.message.get_mut().assume_init_drop() }
+            // &mut self 이므로 다른 스레드와 경쟁하지 않는다.
+            if self.ready.load(Relaxed) {
+                self.message.with_mut(|m| unsafe { (*m).assume_init_drop() })
             }
         }
     }
@@ -65,19 +501,250 @@ pub mod unsafe_channel {
         });
         assert_eq!(channel.receive(), "hello world!");
     }
+
+    // 아래 테스트들은 `cargo +nightly miri test ch5::channel` 로 Miri 에서도 돌릴 수 있다.
+    // 힙에 있는 String 을 주고받아서, 메시지를 두 번 읽거나 해제된 메모리를 읽으면 Miri 가 잡아낸다.
+    #[cfg(all(test, not(loom)))]
+    mod tests {
+        use std::sync::atomic::Ordering::Relaxed;
+        use std::thread;
+        use crate::ch5::channel::error::ChannelError;
+        use crate::ch5::drop_counter::DropCounter;
+        use super::Channel;
+
+        #[test]
+        fn send_receive_across_threads() {
+            let channel = Channel::new();
+            thread::scope(|s| {
+                s.spawn(|| channel.send(String::from("hello world!")));
+                let message = loop {
+                    match channel.try_receive() {
+                        Ok(message) => break message,
+                        Err(_) => thread::yield_now(),
+                    }
+                };
+                assert_eq!(message, "hello world!");
+            });
+        }
+
+        #[test]
+        fn try_receive_empty() {
+            let channel = Channel::new();
+            assert_eq!(channel.try_receive(), Err(ChannelError::Empty));
+            channel.send(1);
+            assert_eq!(channel.try_receive(), Ok(1));
+            assert_eq!(channel.try_receive(), Err(ChannelError::Empty));
+        }
+
+        #[test]
+        fn unreceived_message_dropped_once() {
+            let (message, drops) = DropCounter::new();
+            let channel = Channel::new();
+            channel.send(message);
+            drop(channel);
+            assert_eq!(drops.load(Relaxed), 1);
+        }
+
+        #[test]
+        fn received_message_dropped_once() {
+            let (message, drops) = DropCounter::new();
+            let channel = Channel::new();
+            channel.send(message);
+            drop(channel.receive());
+            drop(channel);
+            assert_eq!(drops.load(Relaxed), 1);
+        }
+
+        #[test]
+        fn panicking_send_poisons_channel() {
+            let channel = Channel::<String>::new();
+            let result = thread::scope(|s| {
+                s.spawn(|| channel.send_with(|| panic!("failed to build message"))).join()
+            });
+            assert!(result.is_err());
+            assert!(channel.is_poisoned());
+            assert_eq!(channel.try_receive(), Err(ChannelError::Poisoned));
+            assert_eq!(channel.try_send(String::from("too late")), Err(String::from("too late")));
+        }
+
+        #[test]
+        fn send_with_builds_message() {
+            let channel = Channel::new();
+            channel.send_with(|| String::from("hello world!"));
+            assert!(!channel.is_poisoned());
+            assert_eq!(channel.receive(), "hello world!");
+        }
+
+        #[test]
+        fn try_send_returns_message_on_second_send() {
+            let channel = Channel::new();
+            assert_eq!(channel.try_send(String::from("first")), Ok(()));
+            assert_eq!(channel.try_send(String::from("second")), Err(String::from("second")));
+            assert_eq!(channel.receive(), "first");
+        }
+    }
+}
+
+// 수업용: ready 플래그에 쓸 메모리 순서를 만들 때 고를 수 있는 unsafe_channel.
+// Release/Acquire 대신 Relaxed 를 고르면 receiver 가 ready 를 보고도 메시지를 못 볼 수 있다(data race, UB).
+// x86 에서는 거의 드러나지 않으므로 loom 으로 확인한다:
+// `RUSTFLAGS="--cfg loom" cargo test --release --test loom unsafe_channel_ordered`
+pub mod unsafe_channel_ordered {
+    use std::mem::MaybeUninit;
+    use std::sync::atomic::Ordering::{self, AcqRel, Acquire, Relaxed, Release};
+    use crate::ch5::channel::error::ChannelError;
+
+    #[cfg(not(loom))]
+    use std::cell::UnsafeCell;
+    #[cfg(not(loom))]
+    use std::sync::atomic::AtomicBool;
+    #[cfg(not(loom))]
+    use super::unsafe_channel::WithMut;
+
+    #[cfg(loom)]
+    use loom::cell::UnsafeCell;
+    #[cfg(loom)]
+    use loom::sync::atomic::AtomicBool;
+
+    pub struct Channel<T> {
+        message: UnsafeCell<MaybeUninit<T>>,
+        in_use: AtomicBool,
+        ready: AtomicBool,
+        store: Ordering,
+        load: Ordering,
+    }
+
+    unsafe impl<T> Sync for Channel<T> where T: Send {}
+
+    impl<T> Channel<T> {
+        pub fn new() -> Self {
+            Self::new_with_ordering(Release, Acquire)
+        }
+
+        // store 는 send 가 ready 를 true 로 바꿀 때, load 는 receive 가 ready 를 읽을 때 쓰인다.
+        pub fn new_with_ordering(store: Ordering, load: Ordering) -> Self {
+            assert!(!matches!(store, Acquire | AcqRel), "invalid ordering for a store");
+            assert!(!matches!(load, Release | AcqRel), "invalid ordering for a load");
+            Self {
+                message: UnsafeCell::new(MaybeUninit::uninit()),
+                in_use: AtomicBool::new(false),
+                ready: AtomicBool::new(false),
+                store,
+                load,
+            }
+        }
+
+        pub fn send(&self, message: T) -> Result<(), ChannelError> {
+            if self.in_use.swap(true, Relaxed) {
+                return Err(ChannelError::AlreadySent);
+            }
+            self.message.with_mut(|m| unsafe { (*m).write(message) });
+            self.ready.store(true, self.store);
+            Ok(())
+        }
+
+        pub fn is_ready(&self) -> bool {
+            self.ready.load(Relaxed)
+        }
+
+        pub fn try_receive(&self) -> Result<T, ChannelError> {
+            if !self.ready.swap(false, self.load) {
+                return Err(ChannelError::Empty);
+            }
+            Ok(self.message.with_mut(|m| unsafe { (*m).assume_init_read() }))
+        }
+    }
+
+    impl<T> Default for Channel<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<T> Drop for Channel<T> {
+        fn drop(&mut self) {
+            if self.ready.load(Relaxed) {
+                self.message.with_mut(|m| unsafe { (*m).assume_init_drop() })
+            }
+        }
+    }
+
+    #[cfg(all(test, not(loom)))]
+    mod tests {
+        use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+        use std::sync::atomic::Ordering;
+        use std::thread;
+        use crate::ch5::channel::error::ChannelError;
+        use super::Channel;
+
+        fn stress(store: Ordering, load: Ordering) {
+            for i in 0..10_000u64 {
+                let channel = Channel::new_with_ordering(store, load);
+                thread::scope(|s| {
+                    s.spawn(|| channel.send([i; 4]).unwrap());
+                    let message = loop {
+                        match channel.try_receive() {
+                            Ok(message) => break message,
+                            Err(_) => thread::yield_now(),
+                        }
+                    };
+                    assert_eq!(message, [i; 4]);
+                });
+            }
+        }
+
+        #[test]
+        fn release_acquire_stress() {
+            stress(Release, Acquire);
+        }
+
+        // Relaxed 는 UB 이므로 기본으로는 돌리지 않는다.
+        // ARM 같은 weakly-ordered 플랫폼에서 `--ignored` 로 돌리면 잘못된 메시지를 볼 수 있다.
+        #[test]
+        #[ignore]
+        fn relaxed_stress() {
+            stress(Relaxed, Relaxed);
+        }
+
+        #[test]
+        fn second_send_and_empty_receive_fail() {
+            let channel = Channel::new();
+            assert_eq!(channel.try_receive(), Err(ChannelError::Empty));
+            assert_eq!(channel.send(1), Ok(()));
+            assert_eq!(channel.send(2), Err(ChannelError::AlreadySent));
+            assert_eq!(channel.try_receive(), Ok(1));
+        }
+
+        #[test]
+        #[should_panic(expected = "invalid ordering for a store")]
+        fn acquire_store_rejected() {
+            Channel::<()>::new_with_ordering(Acquire, Acquire);
+        }
+    }
 }
 
 pub mod safe_channel {
     use std::cell::UnsafeCell;
+    use std::future::Future;
     use std::mem::MaybeUninit;
-    use std::sync::Arc;
+    use std::pin::Pin;
+    use std::ptr;
+    use std::sync::{Arc, Mutex};
     use std::sync::atomic::AtomicBool;
     use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+    use std::task::{Context, Poll, Waker};
     use std::thread;
+    use std::thread::Thread;
+    use crate::ch5::channel::error::ChannelError;
+    use crate::ch5::channel::metrics;
 
     struct Channel<T> {
         message: UnsafeCell<MaybeUninit<T>>,
         ready: AtomicBool,
+        // recv_async 가 기다리는 동안 등록해 두는 waker.
+        waker: Mutex<Option<Waker>>,
+        // channel() 을 호출한 스레드. send 가 unpark 하므로 wait_ready 는 이 스레드에서만 쓸 수 있다.
+        receiving_thread: Thread,
     }
 
     pub struct Sender<T> {
@@ -94,6 +761,8 @@ pub mod safe_channel {
         let a = Arc::new(Channel {
             message: UnsafeCell::new(MaybeUninit::uninit()),
             ready: AtomicBool::new(false),
+            waker: Mutex::new(None),
+            receiving_thread: thread::current(),
         });
         (Sender { channel: a.clone() }, Receiver { channel: a })
     }
@@ -102,6 +771,12 @@ pub mod safe_channel {
         pub fn send(self, message: T) {
             unsafe { (*self.channel.message.get()).write(message) };
             self.channel.ready.store(true, Release);
+            if let Some(waker) = self.channel.waker.lock().unwrap().take() {
+                metrics::unpark();
+                waker.wake();
+            }
+            metrics::unpark();
+            self.channel.receiving_thread.unpark();
         }
     }
 
@@ -110,11 +785,86 @@ pub mod safe_channel {
             self.channel.ready.load(Relaxed)
         }
 
-        pub fn receive(self) -> T {
+        // 메시지가 올 때까지 park 한다. channel() 을 호출한 스레드만 기다릴 수 있다.
+        // 다른 스레드가 기다리면 아무도 깨워 주지 않으므로 panic 한다.
+        pub fn wait_ready(&self) {
+            assert!(
+                thread::current().id() == self.channel.receiving_thread.id(),
+                "wait_ready must be called from the thread that created the channel"
+            );
+            while !self.is_ready() {
+                metrics::park();
+                thread::park();
+            }
+        }
+
+        // 아직 메시지가 없으면 Empty 를 돌려준다. receiver 는 소비되므로 기다리려면 wait_ready 를 먼저 부른다.
+        pub fn receive(self) -> Result<T, ChannelError> {
+            self.try_receive()
+        }
+
+        // receive 와 같지만, 메시지를 돌려주지 않고 out 에 바로 복사한다.
+        // 큰 메시지를 반환값으로 한 번 더 옮기지 않아도 된다.
+        // 채널 쪽 메시지는 ready 를 false 로 바꿨으므로 Channel 의 Drop 이 다시 drop 하지 않는다.
+        // out 에 원래 들어 있던 값은 MaybeUninit::write 처럼 drop 하지 않고 덮어쓴다.
+        pub fn receive_into(self, out: &mut MaybeUninit<T>) -> Result<(), ChannelError> {
             if !self.channel.ready.swap(false, Acquire) {
-                panic!("no message available!");
+                return Err(ChannelError::Empty);
             }
-            unsafe { (*self.channel.message.get()).assume_init_read() }
+            unsafe { ptr::copy_nonoverlapping((*self.channel.message.get()).as_ptr(), out.as_mut_ptr(), 1) };
+            Ok(())
+        }
+
+        pub fn recv_async(self) -> impl Future<Output = T> {
+            Receive { receiver: Some(self) }
+        }
+
+        // receive 와 달리 self 를 빌리기만 하므로 반복해서 polling 할 수 있다.
+        pub fn try_receive(&self) -> Result<T, ChannelError> {
+            if !self.channel.ready.swap(false, Acquire) {
+                return Err(ChannelError::Empty);
+            }
+            Ok(unsafe { (*self.channel.message.get()).assume_init_read() })
+        }
+
+        // ready 를 false 로 바꾸지 않고 메시지를 빌려준다.
+        // Acquire 로 읽어야 sender 가 쓴 메시지가 보인다.
+        // peek 한 뒤에 receive 하는 것은 괜찮지만, 두 스레드가 동시에 receive 하는 것은 안 된다.
+        // (receive 가 self 를 소비하므로 안전한 코드로는 그렇게 할 수 없다.)
+        // try_receive 는 &self 로 메시지를 꺼내므로, 빌려준 참조가 남아 있는 동안 호출되지 않도록 &mut self 를 받는다.
+        pub fn peek(&mut self) -> Option<&T> {
+            if !self.channel.ready.load(Acquire) {
+                return None;
+            }
+            Some(unsafe { (*self.channel.message.get()).assume_init_ref() })
+        }
+    }
+
+    // 메시지를 꺼낸 뒤에는 receiver 를 None 으로 바꿔서 다시 poll 해도 두 번 읽지 않게 한다.
+    struct Receive<T> {
+        receiver: Option<Receiver<T>>,
+    }
+
+    impl<T> Future for Receive<T> {
+        type Output = T;
+
+        fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+            let this = self.get_mut();
+            let receiver = this.receiver.as_ref().expect("polled after completion");
+            let channel = &receiver.channel;
+
+            if !channel.ready.swap(false, Acquire) {
+                *channel.waker.lock().unwrap() = Some(cx.waker().clone());
+
+                // waker 를 등록하기 전에 send 가 끝났을 수도 있으므로 한 번 더 확인한다.
+                if !channel.ready.swap(false, Acquire) {
+                    return Poll::Pending;
+                }
+            }
+
+            let message = unsafe { (*channel.message.get()).assume_init_read() };
+            this.receiver = None;
+            Poll::Ready(message)
         }
     }
 
@@ -129,28 +879,192 @@ pub mod safe_channel {
     pub fn run() {
         thread::scope(|s| {
             let (sender, receiver) = channel();
-            let t = thread::current();
             s.spawn(move || {
                 sender.send("hello world!");
-                t.unpark();
             });
-            while !receiver.is_ready() {
-                thread::park();
-            }
+            receiver.wait_ready();
 
-            println!("{}", receiver.receive());
-            // assert_eq!(receiver.receive(), "hello world!");
+            println!("{}", receiver.receive().unwrap());
+            // assert_eq!(receiver.receive(), Ok("hello world!"));
         });
     }
-}
 
+    #[cfg(test)]
+    mod tests {
+        use std::future::Future;
+        use std::mem::MaybeUninit;
+        use std::pin::pin;
+        use std::sync::Arc;
+        use std::sync::atomic::Ordering::Relaxed;
+        use std::task::{Context, Poll, Wake};
+        use std::thread::{self, Thread};
+        use std::time::Duration;
+        use crate::ch5::channel::error::ChannelError;
+        use crate::ch5::channel::metrics;
+        use crate::ch5::drop_counter::DropCounter;
+        use super::channel;
 
-pub mod safe_channel_without_arc {
-    use std::cell::UnsafeCell;
-    use std::mem::MaybeUninit;
-    use std::sync::atomic::AtomicBool;
-    use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
-    use std::thread;
+        // 현재 스레드를 park 했다가 wake 될 때 unpark 하는 최소한의 executor.
+        struct ThreadWaker(Thread);
+
+        impl Wake for ThreadWaker {
+            fn wake(self: Arc<Self>) {
+                metrics::unpark();
+                self.0.unpark();
+            }
+        }
+
+        fn block_on<F: Future>(future: F) -> F::Output {
+            let mut future = pin!(future);
+            let waker = Arc::new(ThreadWaker(thread::current())).into();
+            let mut cx = Context::from_waker(&waker);
+            loop {
+                match future.as_mut().poll(&mut cx) {
+                    Poll::Ready(output) => return output,
+                    Poll::Pending => {
+                        metrics::park();
+                        thread::park();
+                    }
+                }
+            }
+        }
+
+        #[test]
+        fn recv_async_resolves() {
+            let (sender, receiver) = channel();
+            thread::scope(|s| {
+                s.spawn(move || {
+                    thread::sleep(Duration::from_millis(10));
+                    sender.send("hello world!");
+                });
+                assert_eq!(block_on(receiver.recv_async()), "hello world!");
+            });
+        }
+
+        #[test]
+        fn send_receive_across_threads() {
+            let (sender, receiver) = channel();
+            thread::scope(|s| {
+                s.spawn(move || sender.send(String::from("hello world!")));
+                receiver.wait_ready();
+                assert_eq!(receiver.receive(), Ok(String::from("hello world!")));
+            });
+        }
+
+        #[test]
+        fn unreceived_message_dropped_once() {
+            let (message, drops) = DropCounter::new();
+            let (sender, receiver) = channel();
+            sender.send(message);
+            drop(receiver);
+            assert_eq!(drops.load(Relaxed), 1);
+        }
+
+        #[test]
+        fn received_message_dropped_once() {
+            let (message, drops) = DropCounter::new();
+            let (sender, receiver) = channel();
+            sender.send(message);
+            drop(receiver.receive().unwrap());
+            assert_eq!(drops.load(Relaxed), 1);
+        }
+
+        #[test]
+        fn try_receive_polling() {
+            let (sender, receiver) = channel();
+            thread::scope(|s| {
+                s.spawn(move || {
+                    sender.send("hello world!");
+                });
+
+                let message = loop {
+                    if let Ok(message) = receiver.try_receive() {
+                        break message;
+                    }
+                    std::hint::spin_loop();
+                };
+                assert_eq!(message, "hello world!");
+            });
+            assert_eq!(receiver.try_receive(), Err(ChannelError::Empty));
+        }
+
+        #[test]
+        fn wait_ready_blocks_until_sent() {
+            let (sender, receiver) = channel();
+            thread::scope(|s| {
+                s.spawn(move || {
+                    thread::sleep(Duration::from_millis(10));
+                    sender.send("hello world!");
+                });
+                receiver.wait_ready();
+                assert!(receiver.is_ready());
+                assert_eq!(receiver.receive(), Ok("hello world!"));
+            });
+        }
+
+        #[test]
+        fn wait_ready_from_other_thread_panics() {
+            let (_sender, receiver) = channel::<()>();
+            let result = thread::spawn(move || receiver.wait_ready()).join();
+            assert!(result.is_err());
+        }
+
+        #[test]
+        fn peek_then_receive() {
+            let (sender, mut receiver) = channel();
+            assert_eq!(receiver.peek(), None);
+            sender.send(String::from("hello world!"));
+            assert_eq!(receiver.peek().map(String::as_str), Some("hello world!"));
+            assert!(receiver.is_ready());
+            assert_eq!(receiver.receive().as_deref(), Ok("hello world!"));
+        }
+
+        #[test]
+        fn receive_before_send_is_empty() {
+            let (_sender, receiver) = channel::<u32>();
+            assert_eq!(receiver.receive(), Err(ChannelError::Empty));
+            let (_sender, receiver) = channel::<u32>();
+            let mut out = MaybeUninit::uninit();
+            assert_eq!(receiver.receive_into(&mut out), Err(ChannelError::Empty));
+        }
+
+        #[test]
+        fn receive_into_stack_slot() {
+            let (sender, receiver) = channel();
+            thread::scope(|s| {
+                s.spawn(move || sender.send(String::from("hello world!")));
+                receiver.wait_ready();
+                let mut out = MaybeUninit::<String>::uninit();
+                receiver.receive_into(&mut out).unwrap();
+                let message = unsafe { out.assume_init() };
+                assert_eq!(message, "hello world!");
+            });
+        }
+
+        #[test]
+        fn receive_into_dropped_once() {
+            let (message, drops) = DropCounter::new();
+            let (sender, receiver) = channel();
+            sender.send(message);
+            let mut out = MaybeUninit::uninit();
+            receiver.receive_into(&mut out).unwrap();
+            // receiver 와 함께 Channel 도 drop 되었지만 메시지는 아직 out 에 살아 있다.
+            assert_eq!(drops.load(Relaxed), 0);
+            drop(unsafe { out.assume_init() });
+            assert_eq!(drops.load(Relaxed), 1);
+        }
+    }
+}
+
+
+pub mod safe_channel_without_arc {
+    use std::cell::UnsafeCell;
+    use std::mem::MaybeUninit;
+    use std::ops::Deref;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+    use std::thread;
+    use crate::ch5::channel::error::ChannelError;
 
     pub struct Channel<T> {
         message: UnsafeCell<MaybeUninit<T>>,
@@ -167,6 +1081,12 @@ pub mod safe_channel_without_arc {
         channel: &'a Channel<T>,
     }
 
+    impl<T> Default for Channel<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
     impl<T> Channel<T> {
         pub const fn new() -> Self {
             Self {
@@ -179,6 +1099,16 @@ pub mod safe_channel_without_arc {
             *self = Self::new();
             (Sender { channel: self }, Receiver { channel: self })
         }
+
+        // split 의 빌림이 끝난 뒤, 받지 않은 메시지를 꺼낸다.
+        // &mut self 이므로 다른 스레드가 접근할 수 없어 atomic 연산이 필요 없다.
+        pub fn drain(&mut self) -> Option<T> {
+            if !*self.ready.get_mut() {
+                return None;
+            }
+            *self.ready.get_mut() = false;
+            Some(unsafe { self.message.get_mut().assume_init_read() })
+        }
     }
 
     impl<T> Sender<'_, T> {
@@ -188,16 +1118,55 @@ pub mod safe_channel_without_arc {
         }
     }
 
-    impl<T> Receiver<'_, T> {
+    impl<'a, T> Receiver<'a, T> {
         pub fn is_ready(&self) -> bool {
             self.channel.ready.load(Relaxed)
         }
 
-        pub fn receive(self) -> T {
+        pub fn receive(self) -> Result<T, ChannelError> {
+            if !self.channel.ready.swap(false, Acquire) {
+                return Err(ChannelError::Empty);
+            }
+            Ok(unsafe { (*self.channel.message.get()).assume_init_read() })
+        }
+
+        // 메시지가 아직 없으면 Empty 대신 receiver 를 돌려주어 다시 시도할 수 있게 한다.
+        // ready 를 false 로 바꾸는 것은 receiver 뿐이므로, 한 번 true 를 본 뒤의 swap 은 항상 성공한다.
+        pub fn try_receive(self) -> Result<T, Receiver<'a, T>> {
+            if !self.channel.ready.load(Relaxed) {
+                return Err(self);
+            }
+            self.channel.ready.swap(false, Acquire);
+            Ok(unsafe { (*self.channel.message.get()).assume_init_read() })
+        }
+
+        // 큰 메시지를 옮기지 않고 참조로 읽는다.
+        // ready 는 guard 를 만들 때 Acquire swap 으로 미리 false 로 바꾼다.
+        // 그래야 guard 가 살아 있는 동안 recv_ref 를 다시 불러도 두 번째 guard 가 생기지 않고,
+        // Channel 의 Drop 도 guard 가 drop 할 메시지를 다시 drop 하지 않는다.
+        pub fn recv_ref(&self) -> Option<MessageGuard<'_, T>> {
             if !self.channel.ready.swap(false, Acquire) {
-                panic!("no message available!");
+                return None;
             }
-            unsafe { (*self.channel.message.get()).assume_init_read() }
+            Some(MessageGuard { channel: self.channel })
+        }
+    }
+
+    // 메시지를 옮기지 않고 빌려준다. guard 가 drop 될 때 메시지도 제자리에서 drop 된다.
+    pub struct MessageGuard<'a, T> {
+        channel: &'a Channel<T>,
+    }
+
+    impl<T> Deref for MessageGuard<'_, T> {
+        type Target = T;
+        fn deref(&self) -> &T {
+            unsafe { (*self.channel.message.get()).assume_init_ref() }
+        }
+    }
+
+    impl<T> Drop for MessageGuard<'_, T> {
+        fn drop(&mut self) {
+            unsafe { (*self.channel.message.get()).assume_init_drop() }
         }
     }
 
@@ -222,55 +1191,276 @@ pub mod safe_channel_without_arc {
                 thread::park();
             }
 
-            assert_eq!(receiver.receive(), "hello world!");
+            assert_eq!(receiver.receive(), Ok("hello world!"));
         });
     }
+
+    #[cfg(test)]
+    mod tests {
+        use std::sync::atomic::Ordering::Relaxed;
+        use std::thread;
+        use crate::ch5::drop_counter::DropCounter;
+        use super::Channel;
+
+        #[test]
+        fn unreceived_message_dropped_once() {
+            let (message, drops) = DropCounter::new();
+            let mut channel = Channel::new();
+            let (sender, _receiver) = channel.split();
+            sender.send(message);
+            drop(channel);
+            assert_eq!(drops.load(Relaxed), 1);
+        }
+
+        #[test]
+        fn received_message_dropped_once() {
+            let (message, drops) = DropCounter::new();
+            let mut channel = Channel::new();
+            let (sender, receiver) = channel.split();
+            sender.send(message);
+            drop(receiver.receive().unwrap());
+            drop(channel);
+            assert_eq!(drops.load(Relaxed), 1);
+        }
+
+        #[test]
+        fn drain_unreceived_message() {
+            let (message, drops) = DropCounter::new();
+            let mut channel = Channel::new();
+            {
+                let (sender, _receiver) = channel.split();
+                sender.send((String::from("hello world!"), message));
+            }
+            let (text, message) = channel.drain().unwrap();
+            assert_eq!(text, "hello world!");
+            assert!(channel.drain().is_none());
+            drop(channel);
+            assert_eq!(drops.load(Relaxed), 0);
+            drop(message);
+            assert_eq!(drops.load(Relaxed), 1);
+        }
+
+        #[test]
+        fn recv_ref_drops_in_place() {
+            let (message, drops) = DropCounter::new();
+            let mut channel = Channel::new();
+            {
+                let (sender, receiver) = channel.split();
+                assert!(receiver.recv_ref().is_none());
+                sender.send((String::from("hello world!"), message));
+
+                let guard = receiver.recv_ref().unwrap();
+                assert_eq!(guard.0, "hello world!");
+                assert!(receiver.recv_ref().is_none());
+                assert_eq!(drops.load(Relaxed), 0);
+                drop(guard);
+
+                assert_eq!(drops.load(Relaxed), 1);
+                assert!(!receiver.is_ready());
+            }
+            assert!(channel.drain().is_none());
+            drop(channel);
+            assert_eq!(drops.load(Relaxed), 1);
+        }
+
+        #[test]
+        fn try_receive_polling() {
+            let mut channel = Channel::new();
+            thread::scope(|s| {
+                let (sender, mut receiver) = channel.split();
+                s.spawn(move || {
+                    sender.send("hello world!");
+                });
+                let message = loop {
+                    match receiver.try_receive() {
+                        Ok(message) => break message,
+                        Err(r) => receiver = r,
+                    }
+                    thread::yield_now();
+                };
+                assert_eq!(message, "hello world!");
+            });
+        }
+    }
 }
 
 pub mod ch5_6 {
     use std::cell::UnsafeCell;
-    use std::marker::PhantomData;
     use std::mem::MaybeUninit;
+    use std::sync::Mutex;
     use std::sync::atomic::AtomicBool;
     use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
     use std::thread;
-    use std::thread::Thread;
+    use std::time::{Duration, Instant};
+    use crate::ch2::shutdown::ShutdownToken;
+    use crate::ch5::channel::error::ChannelError;
+    use crate::ch5::channel::metrics;
+    use crate::ch5::channel::parker::{Parker, Unparker};
 
     pub struct Channel<T> {
         message: UnsafeCell<MaybeUninit<T>>,
         ready: AtomicBool,
+        // send 시점에 깨울 receiver. split 할 때 Receiver 의 Parker 로 정해지지만 select2 가 바꿀 수 있다.
+        unparker: Mutex<Option<Unparker>>,
+        sender_alive: AtomicBool,
     }
 
     unsafe impl<T> Sync for Channel<T> where T: Send {}
 
     pub struct Sender<'a, T> {
         channel: &'a Channel<T>,
-        receiving_thread: Thread,
     }
 
+    // thread::park 대신 자기 Parker 로 기다리므로, 다른 스레드로 옮겨서 받아도 된다.
     pub struct Receiver<'a, T> {
         channel: &'a Channel<T>,
-        _no_send: PhantomData<*const ()>,
+        parker: Parker,
     }
 
     impl<T> Sender<'_, T> {
+        // receiver 를 깨우는 것은 send 가 끝나며 실행되는 drop 이 한다.
         pub fn send(self, message: T) {
             unsafe { (*self.channel.message.get()).write(message) };
             self.channel.ready.store(true, Release);
-            self.receiving_thread.unpark();
+            metrics::send(true);
         }
     }
 
-    impl<T> Receiver<'_, T> {
+    impl<T> Drop for Sender<'_, T> {
+        fn drop(&mut self) {
+            self.channel.sender_alive.store(false, Release);
+            self.channel.unpark_receiver();
+        }
+    }
+
+    impl<'a, T> Receiver<'a, T> {
         pub fn is_ready(&self) -> bool {
             self.channel.ready.load(Relaxed)
         }
 
-        pub fn receive(self) -> T {
+        pub fn receive(self) -> Result<T, ChannelError> {
+            loop {
+                if let Some(message) = self.try_take() {
+                    return Ok(message);
+                }
+                // sender 가 send 한 뒤에 drop 되었을 수도 있으므로 한 번 더 확인한다.
+                if !self.channel.sender_alive.load(Acquire) {
+                    return self.try_take().ok_or(ChannelError::Disconnected);
+                }
+                metrics::park();
+                self.parker.park();
+            }
+        }
+
+        // 메시지가 오거나 stop 이 trigger 될 때까지 기다린다. 둘 다 일어났다면 메시지를 돌려준다.
+        // Cancelled 를 돌려줄 때 메시지는 꺼내지 않으므로, 늦게 도착한 메시지는 채널에 남는다.
+        // Parker 는 send 와 trigger 양쪽에 등록되어 있고, park 하기 전에 두 조건을 다시 확인하므로
+        // 그 사이에 온 unpark 는 다음 park 를 바로 돌아오게 한다.
+        // 돌아가기 전에 등록을 지우므로, 같은 stop 을 여러 채널에 계속 써도 대기 목록이 쌓이지 않는다.
+        pub fn receive_cancellable(self, stop: &ShutdownToken) -> Result<T, ChannelError> {
+            let unparker = self.parker.unparker();
+            stop.register_unparker(unparker.clone());
+            let result = loop {
+                if let Some(message) = self.try_take() {
+                    break Ok(message);
+                }
+                if stop.is_triggered() {
+                    break Err(ChannelError::Cancelled);
+                }
+                if !self.channel.sender_alive.load(Acquire) {
+                    break self.try_take().ok_or(ChannelError::Disconnected);
+                }
+                metrics::park();
+                self.parker.park();
+            };
+            stop.deregister_unparker(&unparker);
+            result
+        }
+
+        // park 하기 전에 spin_count 번까지 ready 를 확인한다.
+        // sender 가 곧 보낼 것이라면 park/unpark 의 시스템 콜 비용을 아낄 수 있다.
+        pub fn receive_spin(self, spin_count: u32) -> Result<T, ChannelError> {
+            for _ in 0..spin_count {
+                if self.channel.ready.load(Relaxed) {
+                    break;
+                }
+                std::hint::spin_loop();
+            }
+            self.receive()
+        }
+
+        // 시간 안에 메시지가 오지 않으면 다시 시도할 수 있도록 receiver 를 TimedOut 과 함께 돌려준다.
+        // sender 가 보내지 않고 사라졌다면 기다려도 소용없으므로 시간이 남았어도 바로 Disconnected 와 함께 돌려준다.
+        // park_timeout 은 일찍 깨어날 수 있으므로 매번 ready 와 남은 시간을 다시 확인한다.
+        pub fn receive_timeout(self, dur: Duration) -> Result<T, (Receiver<'a, T>, ChannelError)> {
+            let deadline = Instant::now() + dur;
+            loop {
+                if let Some(message) = self.try_take() {
+                    return Ok(message);
+                }
+                // receive 와 같이, send 한 뒤에 drop 되었을 수도 있으므로 한 번 더 확인한다.
+                if !self.channel.sender_alive.load(Acquire) {
+                    return match self.try_take() {
+                        Some(message) => Ok(message),
+                        None => Err((self, ChannelError::Disconnected)),
+                    };
+                }
+                let now = Instant::now();
+                if now >= deadline {
+                    return Err((self, ChannelError::TimedOut));
+                }
+                metrics::park();
+                self.parker.park_timeout(deadline - now);
+            }
+        }
+
+        // 이후의 send 가 split 때 정해진 이 receiver 의 Parker 대신 unparker 를 깨우도록 바꾼다.
+        // select2 는 이것으로 두 채널의 send 가 모두 같은 Parker 를 깨우게 한다.
+        fn register(&self, unparker: Unparker) {
+            *self.channel.unparker.lock().unwrap() = Some(unparker);
+        }
+
+        fn try_take(&self) -> Option<T> {
             if !self.channel.ready.swap(false, Acquire) {
-                thread::park();
+                return None;
+            }
+            metrics::receive();
+            Some(unsafe { (*self.channel.message.get()).assume_init_read() })
+        }
+    }
+
+    pub enum Either<A, B> {
+        Left(A),
+        Right(B),
+    }
+
+    // 두 채널 중 먼저 도착한 메시지를 돌려준다.
+    // 두 채널 모두 a 의 Parker 를 깨우게 등록한 뒤 ready 를 확인하므로, 그 사이에 온 send 의 unpark 를 놓치지 않는다.
+    // sender 의 drop 도 같은 Parker 를 깨운다. 한쪽 sender 만 사라졌다면 다른 쪽을 계속 기다리고,
+    // 둘 다 보내지 않고 사라졌다면 Disconnected 를 돌려준다.
+    pub fn select2<A, B>(a: Receiver<A>, b: Receiver<B>) -> Result<Either<A, B>, ChannelError> {
+        b.register(a.parker.unparker());
+        loop {
+            // send 한 뒤에 drop 되었을 수도 있으므로, sender_alive 를 먼저 읽고 ready 를 확인한다.
+            let a_alive = a.channel.sender_alive.load(Acquire);
+            let b_alive = b.channel.sender_alive.load(Acquire);
+            if let Some(message) = a.try_take() {
+                return Ok(Either::Left(message));
             }
-            unsafe { (*self.channel.message.get()).assume_init_read() }
+            if let Some(message) = b.try_take() {
+                return Ok(Either::Right(message));
+            }
+            if !a_alive && !b_alive {
+                return Err(ChannelError::Disconnected);
+            }
+            metrics::park();
+            a.parker.park();
+        }
+    }
+
+    impl<T> Default for Channel<T> {
+        fn default() -> Self {
+            Self::new()
         }
     }
 
@@ -279,19 +1469,39 @@ pub mod ch5_6 {
             Self {
                 message: UnsafeCell::new(MaybeUninit::uninit()),
                 ready: AtomicBool::new(false),
+                unparker: Mutex::new(None),
+                sender_alive: AtomicBool::new(false),
             }
         }
 
-        pub fn split(&mut self) -> (Sender<T>, Receiver<T>) {
-            *self = Self::new();
+        fn unpark_receiver(&self) {
+            if let Some(u) = &*self.unparker.lock().unwrap() {
+                metrics::unpark();
+                u.unpark();
+            }
+        }
+
+        // 읽지 않은 메시지가 남아 있다면 drop 하고 채널을 처음 상태로 되돌린다.
+        // 이미 받은 메시지는 ready 가 false 이므로 다시 drop 되지 않는다.
+        pub fn reset(&mut self) {
+            if *self.ready.get_mut() {
+                unsafe { self.message.get_mut().assume_init_drop() }
+            }
+            *self.ready.get_mut() = false;
+        }
+
+        pub fn split(&mut self) -> (Sender<'_, T>, Receiver<'_, T>) {
+            self.reset();
+            let parker = Parker::new();
+            *self.unparker.get_mut().unwrap() = Some(parker.unparker());
+            *self.sender_alive.get_mut() = true;
             (
                 Sender {
                     channel: self,
-                    receiving_thread: thread::current(),
                 },
                 Receiver {
                     channel: self,
-                    _no_send: PhantomData,
+                    parker,
                 }
             )
         }
@@ -312,7 +1522,2627 @@ pub mod ch5_6 {
             s.spawn(move || {
                 sender.send("hello world!");
             });
-            assert_eq!(receiver.receive(), "hello world!");
+            assert_eq!(receiver.receive(), Ok("hello world!"));
         })
     }
-}
\ No newline at end of file
+
+    #[cfg(test)]
+    mod tests {
+        use std::sync::atomic::AtomicBool;
+        use std::sync::atomic::Ordering::Relaxed;
+        use std::thread;
+        use std::time::{Duration, Instant};
+        use crate::ch2::shutdown::ShutdownToken;
+        use crate::ch3::hb::ShadowClock;
+        use crate::ch5::drop_counter::DropCounter;
+        use super::{select2, Channel, ChannelError, Either};
+
+        #[test]
+        fn reset_and_reuse() {
+            let mut channel = Channel::new();
+
+            thread::scope(|s| {
+                let (sender, receiver) = channel.split();
+                s.spawn(move || {
+                    sender.send(String::from("first"));
+                });
+                assert_eq!(receiver.receive(), Ok(String::from("first")));
+            });
+
+            channel.reset();
+
+            thread::scope(|s| {
+                let (sender, receiver) = channel.split();
+                s.spawn(move || {
+                    sender.send(String::from("second"));
+                });
+                assert_eq!(receiver.receive(), Ok(String::from("second")));
+            });
+        }
+
+        // 메시지 없이 receiver 의 Parker 를 깨워도 receive 는 ready 를 볼 때까지 다시 기다려야 한다.
+        #[test]
+        fn spurious_wakeup_before_send() {
+            let mut channel = Channel::new();
+            let sent = AtomicBool::new(false);
+            thread::scope(|s| {
+                let (sender, receiver) = channel.split();
+                let unparker = receiver.parker.unparker();
+                let sent = &sent;
+                s.spawn(move || {
+                    unparker.unpark();
+                    thread::sleep(Duration::from_millis(20));
+                    unparker.unpark();
+                    thread::sleep(Duration::from_millis(20));
+                    sent.store(true, Relaxed);
+                    sender.send("hello world!");
+                });
+                assert_eq!(receiver.receive(), Ok("hello world!"));
+                assert!(sent.load(Relaxed));
+            });
+        }
+
+        #[test]
+        fn sender_dropped_without_send() {
+            let mut channel = Channel::<u32>::new();
+            thread::scope(|s| {
+                let (sender, receiver) = channel.split();
+                s.spawn(move || drop(sender));
+                assert_eq!(receiver.receive(), Err(ChannelError::Disconnected));
+            });
+        }
+
+        #[test]
+        fn select2_second_channel() {
+            let mut a = Channel::<u32>::new();
+            let mut b = Channel::<&str>::new();
+            let (_, receiver_a) = a.split();
+            let (sender_b, receiver_b) = b.split();
+
+            thread::scope(|s| {
+                s.spawn(move || {
+                    thread::sleep(Duration::from_millis(10));
+                    sender_b.send("hello world!");
+                });
+                // 첫 채널의 sender 는 이미 사라졌지만 두 번째 채널을 계속 기다린다.
+                match select2(receiver_a, receiver_b) {
+                    Ok(Either::Left(_)) => panic!("nothing was sent on the first channel"),
+                    Ok(Either::Right(message)) => assert_eq!(message, "hello world!"),
+                    Err(e) => panic!("second sender is still alive: {e}"),
+                }
+            });
+        }
+
+        // receiver 는 어느 스레드로든 옮길 수 있으므로, split 한 스레드가 아닌 곳에서 select2 해도 깨어난다.
+        #[test]
+        fn select2_on_another_thread() {
+            let mut a = Channel::<u32>::new();
+            let mut b = Channel::<&str>::new();
+            let (sender_a, receiver_a) = a.split();
+            let (_sender_b, receiver_b) = b.split();
+
+            thread::scope(|s| {
+                let t = s.spawn(move || select2(receiver_a, receiver_b));
+                thread::sleep(Duration::from_millis(10));
+                sender_a.send(7);
+                match t.join().unwrap() {
+                    Ok(Either::Left(message)) => assert_eq!(message, 7),
+                    Ok(Either::Right(_)) => panic!("nothing was sent on the second channel"),
+                    Err(e) => panic!("first sender is still alive: {e}"),
+                }
+            });
+        }
+
+        #[test]
+        fn select2_both_senders_dropped() {
+            let mut a = Channel::<u32>::new();
+            let mut b = Channel::<&str>::new();
+            let (sender_a, receiver_a) = a.split();
+            let (sender_b, receiver_b) = b.split();
+
+            thread::scope(|s| {
+                let t = s.spawn(move || select2(receiver_a, receiver_b));
+                thread::sleep(Duration::from_millis(10));
+                drop(sender_a);
+                thread::sleep(Duration::from_millis(10));
+                drop(sender_b);
+                assert!(matches!(t.join().unwrap(), Err(ChannelError::Disconnected)));
+            });
+        }
+
+        #[test]
+        fn receive_timeout_without_sender() {
+            let mut channel = Channel::<u32>::new();
+            let (_sender, receiver) = channel.split();
+            assert!(matches!(receiver.receive_timeout(Duration::from_millis(50)), Err((_, ChannelError::TimedOut))));
+        }
+
+        #[test]
+        fn receive_timeout_sender_dropped() {
+            let mut channel = Channel::<u32>::new();
+            let (sender, receiver) = channel.split();
+            drop(sender);
+            let start = Instant::now();
+            assert!(matches!(receiver.receive_timeout(Duration::from_secs(10)), Err((_, ChannelError::Disconnected))));
+            assert!(start.elapsed() < Duration::from_secs(10));
+        }
+
+        // sender 는 첫 timeout 이 끝났다는 신호를 받은 뒤에야 보내므로, 스케줄링과 관계없이 첫 시도는 실패한다.
+        #[test]
+        fn receive_timeout_with_delayed_sender() {
+            let mut channel = Channel::new();
+            let (go, wait_for_go) = std::sync::mpsc::channel();
+            thread::scope(|s| {
+                let (sender, receiver) = channel.split();
+                s.spawn(move || {
+                    wait_for_go.recv().unwrap();
+                    sender.send("hello world!");
+                });
+                let receiver = match receiver.receive_timeout(Duration::from_millis(1)) {
+                    Ok(_) => panic!("message arrived too early"),
+                    Err((receiver, e)) => {
+                        assert_eq!(e, ChannelError::TimedOut);
+                        receiver
+                    }
+                };
+                go.send(()).unwrap();
+                match receiver.receive_timeout(Duration::from_secs(5)) {
+                    Ok(message) => assert_eq!(message, "hello world!"),
+                    Err((_, e)) => panic!("{e}"),
+                }
+            });
+        }
+
+        #[test]
+        fn reset_drops_unread_message() {
+            let (message, drops) = DropCounter::new();
+            let mut channel = Channel::new();
+            {
+                let (sender, _receiver) = channel.split();
+                sender.send(message);
+            }
+            assert_eq!(drops.load(Relaxed), 0);
+
+            channel.reset();
+            assert_eq!(drops.load(Relaxed), 1);
+
+            channel.reset();
+            drop(channel);
+            assert_eq!(drops.load(Relaxed), 1);
+        }
+
+        #[test]
+        fn receive_cancellable_cancelled() {
+            let mut channel = Channel::<u32>::new();
+            let stop = ShutdownToken::new();
+            thread::scope(|s| {
+                let (sender, receiver) = channel.split();
+                s.spawn(|| {
+                    thread::sleep(Duration::from_millis(20));
+                    stop.trigger();
+                });
+                assert_eq!(receiver.receive_cancellable(&stop), Err(ChannelError::Cancelled));
+                // 취소된 뒤에 보낸 메시지는 채널에 남는다.
+                sender.send(1);
+            });
+            assert!(*channel.ready.get_mut());
+        }
+
+        #[test]
+        fn receive_cancellable_send_wins() {
+            let mut channel = Channel::new();
+            let stop = ShutdownToken::new();
+            thread::scope(|s| {
+                let (sender, receiver) = channel.split();
+                s.spawn(move || {
+                    thread::sleep(Duration::from_millis(20));
+                    sender.send(123);
+                });
+                assert_eq!(receiver.receive_cancellable(&stop), Ok(123));
+            });
+            assert!(!stop.is_triggered());
+        }
+
+        #[test]
+        fn receive_spin_immediate_sender() {
+            let mut channel = Channel::new();
+            let (sender, receiver) = channel.split();
+            sender.send(123);
+            assert_eq!(receiver.receive_spin(1000), Ok(123));
+        }
+
+        #[test]
+        fn receive_spin_falls_back_to_park() {
+            let mut channel = Channel::new();
+            thread::scope(|s| {
+                let (sender, receiver) = channel.split();
+                s.spawn(move || {
+                    thread::sleep(Duration::from_millis(20));
+                    sender.send(123);
+                });
+                assert_eq!(receiver.receive_spin(10), Ok(123));
+            });
+        }
+
+        #[test]
+        fn send_receive_across_threads() {
+            let mut channel = Channel::new();
+            thread::scope(|s| {
+                let (sender, receiver) = channel.split();
+                s.spawn(move || sender.send(String::from("hello world!")));
+                assert_eq!(receiver.receive().as_deref(), Ok("hello world!"));
+            });
+        }
+
+        // send 전에 한 Relaxed store 는 receive 가 돌려준 뒤에 보여야 한다.
+        // write 와 ready 의 store 순서가 바뀌거나 ready 가 Relaxed 가 되면 이 검사가 실패할 수 있다.
+        #[test]
+        fn send_happens_before_receive() {
+            for _ in 0..1000 {
+                let clock = ShadowClock::new();
+                let mut channel = Channel::new();
+                thread::scope(|s| {
+                    let (sender, receiver) = channel.split();
+                    s.spawn(|| sender.send(clock.on_store()));
+                    let stamp = receiver.receive().unwrap();
+                    clock.assert_happens_before(stamp);
+                });
+            }
+        }
+
+        // Receiver 는 Parker 로 기다리므로 split 한 스레드가 아닌 곳에서 받아도 깨어난다.
+        #[test]
+        fn receive_on_another_thread() {
+            let mut channel = Channel::new();
+            let (sender, receiver) = channel.split();
+            thread::scope(|s| {
+                let t = s.spawn(move || receiver.receive());
+                thread::sleep(Duration::from_millis(20));
+                sender.send(123);
+                assert_eq!(t.join().unwrap(), Ok(123));
+            });
+        }
+
+        #[test]
+        fn unreceived_message_dropped_once() {
+            let (message, drops) = DropCounter::new();
+            let mut channel = Channel::new();
+            let (sender, _receiver) = channel.split();
+            sender.send(message);
+            drop(channel);
+            assert_eq!(drops.load(Relaxed), 1);
+        }
+
+        #[test]
+        fn received_message_dropped_once() {
+            let (message, drops) = DropCounter::new();
+            let mut channel = Channel::new();
+            let (sender, receiver) = channel.split();
+            sender.send(message);
+            drop(receiver.receive());
+            drop(channel);
+            assert_eq!(drops.load(Relaxed), 1);
+        }
+    }
+}
+
+pub mod bounded {
+    use std::cell::UnsafeCell;
+    use std::mem::MaybeUninit;
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::{fence, AtomicBool, AtomicU32, AtomicU64, AtomicUsize};
+    use std::sync::atomic::Ordering::{Acquire, Relaxed, Release, SeqCst};
+    use std::thread;
+    use std::thread::Thread;
+    use std::time::{Duration, Instant};
+    use crate::ch5::channel::error::ChannelError;
+    use crate::ch5::channel::metrics;
+
+    const EMPTY: usize = usize::MAX;
+    const NIL: u32 = u32::MAX;
+
+    // 비어 있는 메시지 칸 번호를 쌓아 두는 lock-free 스택.
+    // head 의 아래 32비트는 맨 위 칸 번호이고, 위 32비트는 바뀔 때마다 늘어나는 tag 다.
+    // 같은 칸이 빠졌다가 다시 들어와도 tag 가 달라지므로 CAS 가 낡은 next 로 성공하지 않는다(ABA).
+    struct FreeList {
+        head: AtomicU64,
+        next: Box<[AtomicU32]>,
+    }
+
+    impl FreeList {
+        fn new(len: usize) -> Self {
+            assert!(len < NIL as usize, "capacity too large");
+            Self {
+                head: AtomicU64::new(0),
+                next: (0..len)
+                    .map(|i| AtomicU32::new(if i + 1 < len { i as u32 + 1 } else { NIL }))
+                    .collect(),
+            }
+        }
+
+        // Acquire: 칸을 마지막으로 비운 receiver 의 읽기가 이 칸에 쓰기 전에 끝나 있게 한다.
+        // head 는 CAS 로만 바뀌므로, 앞선 모든 release_slot 과 동기화된다.
+        fn acquire_slot(&self) -> Option<usize> {
+            let mut head = self.head.load(Acquire);
+            loop {
+                let index = head as u32;
+                if index == NIL {
+                    return None;
+                }
+                // 다른 스레드가 먼저 가져갔다면 낡은 값일 수 있지만, 그때는 tag 가 달라서 CAS 가 실패한다.
+                let next = self.next[index as usize].load(Relaxed);
+                match self.head.compare_exchange_weak(head, tagged(head, next), Acquire, Acquire) {
+                    Ok(_) => return Some(index as usize),
+                    Err(h) => head = h,
+                }
+            }
+        }
+
+        fn release_slot(&self, index: usize) {
+            let mut head = self.head.load(Relaxed);
+            loop {
+                self.next[index].store(head as u32, Relaxed);
+                match self.head.compare_exchange_weak(head, tagged(head, index as u32), Release, Relaxed) {
+                    Ok(_) => return,
+                    Err(h) => head = h,
+                }
+            }
+        }
+    }
+
+    fn tagged(old: u64, index: u32) -> u64 {
+        ((old >> 32).wrapping_add(1) << 32) | index as u64
+    }
+
+    // 여러 producer 와 하나의 consumer 를 위한 고정 크기 채널.
+    // producer 는 free 에서 메시지 칸을 하나 얻어 쓰고, 그 칸 번호를 queue 의 tail 자리에 넣는다.
+    // consumer 는 queue 의 head 자리에서 칸 번호를 꺼내 읽은 뒤 칸을 free 에 돌려준다.
+    // 얻을 칸이 없으면 가득 찬 것이므로, 쓰고 있는 칸은 capacity 개를 넘지 않는다.
+    pub struct Channel<T> {
+        messages: Box<[UnsafeCell<MaybeUninit<T>>]>,
+        free: FreeList,
+        // 보낸 순서대로의 칸 번호. 아직 안 쓰였거나 이미 받은 자리는 EMPTY 다.
+        queue: Box<[AtomicUsize]>,
+        head: AtomicUsize,
+        tail: AtomicUsize,
+        // 보내는 중이거나 받을 수 있는 메시지의 수
+        len: AtomicUsize,
+        receiving: AtomicBool,
+        // channel() 로 만든 Sender 의 수. Channel 을 직접 쓸 때는 0 이다.
+        senders: AtomicUsize,
+        // Sender::close 로 닫혔는지. 닫힌 뒤의 send 는 실패하고, receiver 는 남은 메시지를 다 받은 뒤 끊긴 것을 본다.
+        closed: AtomicBool,
+        // 메시지가 들어오거나 마지막 Sender 가 사라졌을 때 깨울 스레드.
+        receiving_thread: Mutex<Option<Thread>>,
+        // 가득 차서 send_blocking 에서 기다리는 스레드들과 그 수. recv 는 수가 0 이면 잠금을 잡지 않는다.
+        blocked_senders: Mutex<Vec<Thread>>,
+        num_blocked_senders: AtomicUsize,
+    }
+
+    unsafe impl<T> Sync for Channel<T> where T: Send {}
+
+    pub struct Sender<T> {
+        channel: Arc<Channel<T>>,
+    }
+
+    pub struct Receiver<T> {
+        channel: Arc<Channel<T>>,
+    }
+
+    pub fn channel<T>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+        let a = Arc::new(Channel::new(capacity));
+        a.senders.store(1, Relaxed);
+        (Sender { channel: a.clone() }, Receiver { channel: a })
+    }
+
+    impl<T> Channel<T> {
+        pub fn new(capacity: usize) -> Self {
+            assert!(capacity > 0, "capacity must be non-zero");
+            Self {
+                messages: (0..capacity).map(|_| UnsafeCell::new(MaybeUninit::uninit())).collect(),
+                free: FreeList::new(capacity),
+                queue: (0..capacity).map(|_| AtomicUsize::new(EMPTY)).collect(),
+                head: AtomicUsize::new(0),
+                tail: AtomicUsize::new(0),
+                len: AtomicUsize::new(0),
+                receiving: AtomicBool::new(false),
+                senders: AtomicUsize::new(0),
+                closed: AtomicBool::new(false),
+                receiving_thread: Mutex::new(None),
+                blocked_senders: Mutex::new(Vec::new()),
+                num_blocked_senders: AtomicUsize::new(0),
+            }
+        }
+
+        pub fn capacity(&self) -> usize {
+            self.messages.len()
+        }
+
+        pub fn len(&self) -> usize {
+            self.len.load(Relaxed)
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+
+        pub fn send(&self, message: T) -> Result<(), T> {
+            let Some(slot) = self.free.acquire_slot() else {
+                metrics::send(false);
+                return Err(message);
+            };
+            // SeqCst 는 아래의 closed 확인과 함께 is_disconnected 와 짝을 이룬다.
+            self.len.fetch_add(1, SeqCst);
+            // len 을 늘린 뒤에 확인해야, 닫힌 것을 못 본 send 의 메시지는 receiver 가 반드시 기다려 준다.
+            if self.closed.load(SeqCst) {
+                self.len.fetch_sub(1, Relaxed);
+                self.free.release_slot(slot);
+                self.unpark_receiver();
+                metrics::send(false);
+                return Err(message);
+            }
+
+            unsafe { (*self.messages[slot].get()).write(message) };
+            let tail = self.tail.fetch_add(1, Relaxed);
+            self.publish(tail, slot);
+            metrics::send(true);
+            self.unpark_receiver();
+            Ok(())
+        }
+
+        // 가득 차 있으면 칸이 빌 때까지 기다린다. 채널이 닫혀 있으면 메시지를 돌려준다.
+        pub fn send_blocking(&self, message: T) -> Result<(), T> {
+            self.send_until(message, None).map_err(|(message, _)| message)
+        }
+
+        // send_blocking 과 같지만 timeout 이 지나도록 칸이 비지 않으면 메시지와 TimedOut 을 돌려준다.
+        // 닫힌 채널에는 기다려도 보낼 수 없으므로 시간이 남았어도 바로 Disconnected 로 돌려준다.
+        pub fn send_timeout(&self, message: T, timeout: Duration) -> Result<(), (T, ChannelError)> {
+            self.send_until(message, Some(Instant::now() + timeout))
+        }
+
+        // deadline 이 None 이면 보낼 때까지 기다린다.
+        fn send_until(&self, mut message: T, deadline: Option<Instant>) -> Result<(), (T, ChannelError)> {
+            loop {
+                match self.send(message) {
+                    Ok(()) => return Ok(()),
+                    Err(m) => message = m,
+                }
+                if self.is_closed() {
+                    return Err((message, ChannelError::Disconnected));
+                }
+                {
+                    let mut blocked = self.blocked_senders.lock().unwrap();
+                    blocked.push(thread::current());
+                    self.num_blocked_senders.store(blocked.len(), Relaxed);
+                }
+                // unpark_senders 의 fence 와 짝을 이룬다. recv 가 칸을 비운 것을 여기서 못 보면,
+                // recv 는 반드시 등록된 이 스레드를 보고 깨운다.
+                fence(SeqCst);
+                match self.send(message) {
+                    // 목록에 남은 항목은 나중에 한 번 더 unpark 될 뿐이다.
+                    Ok(()) => return Ok(()),
+                    Err(m) => message = m,
+                }
+                if self.is_closed() {
+                    return Err((message, ChannelError::Disconnected));
+                }
+                match deadline {
+                    None => {
+                        metrics::park();
+                        thread::park();
+                    }
+                    Some(deadline) => {
+                        // 깨어난 이유가 가짜일 수 있으므로, 남은 시간은 매번 deadline 에서 다시 계산한다.
+                        let now = Instant::now();
+                        if now >= deadline {
+                            return Err((message, ChannelError::TimedOut));
+                        }
+                        metrics::park();
+                        thread::park_timeout(deadline - now);
+                    }
+                }
+            }
+        }
+
+        pub fn recv(&self) -> Option<T> {
+            // consumer 는 하나라고 가정하지만, 동시에 호출되더라도 안전하도록 직렬화한다.
+            while self.receiving.swap(true, Acquire) {
+                std::hint::spin_loop();
+            }
+
+            let head = self.head.load(Relaxed);
+            let message = self.take(head);
+            if message.is_some() {
+                self.head.store(head + 1, Relaxed);
+                self.len.fetch_sub(1, Release);
+            }
+
+            self.receiving.store(false, Release);
+            if message.is_some() {
+                self.unpark_senders();
+            }
+            message
+        }
+
+        // 들어갈 수 있는 만큼 items 의 앞에서부터 보내고, 보낸 개수를 돌려준다. 보낸 항목은 items 에서 빠진다.
+        // len 과 tail 은 묶음 전체에 대해 한 번씩만 갱신한다.
+        // send 와 같이 metrics 에는 보내지 못하고 items 에 남은 항목마다 실패한 send 를 하나씩 센다.
+        pub fn send_slice(&self, items: &mut Vec<T>) -> usize {
+            let slots: Vec<usize> = (0..items.len()).map_while(|_| self.free.acquire_slot()).collect();
+            let count = slots.len();
+            if count == 0 {
+                items.iter().for_each(|_| metrics::send(false));
+                return 0;
+            }
+            self.len.fetch_add(count, SeqCst);
+            if self.closed.load(SeqCst) {
+                self.len.fetch_sub(count, Relaxed);
+                for slot in slots {
+                    self.free.release_slot(slot);
+                }
+                items.iter().for_each(|_| metrics::send(false));
+                self.unpark_receiver();
+                return 0;
+            }
+
+            let tail = self.tail.fetch_add(count, Relaxed);
+            for (i, (message, slot)) in items.drain(..count).zip(slots).enumerate() {
+                unsafe { (*self.messages[slot].get()).write(message) };
+                self.publish(tail + i, slot);
+                metrics::send(true);
+            }
+            items.iter().for_each(|_| metrics::send(false));
+            self.unpark_receiver();
+            count
+        }
+
+        // 준비된 메시지를 최대 max 개까지 순서대로 꺼낸다. head 와 len 은 마지막에 한 번만 갱신한다.
+        pub fn recv_batch(&self, max: usize) -> Vec<T> {
+            while self.receiving.swap(true, Acquire) {
+                std::hint::spin_loop();
+            }
+
+            let head = self.head.load(Relaxed);
+            let mut messages = Vec::new();
+            while messages.len() < max {
+                match self.take(head + messages.len()) {
+                    Some(message) => messages.push(message),
+                    None => break,
+                }
+            }
+            if !messages.is_empty() {
+                self.head.store(head + messages.len(), Relaxed);
+                self.len.fetch_sub(messages.len(), Release);
+            }
+
+            self.receiving.store(false, Release);
+            if !messages.is_empty() {
+                self.unpark_senders();
+            }
+            messages
+        }
+
+        // position 자리에 slot 을 넣는다. 한 바퀴 전의 메시지를 receiver 가 아직 비우는 중이면 기다린다.
+        fn publish(&self, position: usize, slot: usize) {
+            let entry = &self.queue[position % self.capacity()];
+            while entry.load(Acquire) != EMPTY {
+                std::hint::spin_loop();
+            }
+            // Release: 메시지를 쓴 것이 receiver 에게 보이게 한다.
+            entry.store(slot, Release);
+        }
+
+        // position 자리의 메시지를 꺼내고 칸을 돌려준다. 아직 안 쓰였으면 None. receiving 을 쥔 채로 호출한다.
+        fn take(&self, position: usize) -> Option<T> {
+            let entry = &self.queue[position % self.capacity()];
+            let slot = entry.load(Acquire);
+            if slot == EMPTY {
+                return None;
+            }
+            let message = unsafe { (*self.messages[slot].get()).assume_init_read() };
+            entry.store(EMPTY, Release);
+            self.free.release_slot(slot);
+            metrics::receive();
+            Some(message)
+        }
+
+        pub fn close(&self) {
+            self.closed.store(true, SeqCst);
+            self.unpark_receiver();
+            self.unpark_senders();
+        }
+
+        pub fn is_closed(&self) -> bool {
+            self.closed.load(Relaxed)
+        }
+
+        // 더 올 메시지가 없는지. 닫혔더라도 보내는 중인 메시지가 남아 있으면 그것이 쓰이기를 기다려야 한다.
+        fn is_disconnected(&self) -> bool {
+            self.senders.load(Acquire) == 0 || (self.closed.load(SeqCst) && self.len.load(SeqCst) == 0)
+        }
+
+        fn unpark_receiver(&self) {
+            if let Some(t) = &*self.receiving_thread.lock().unwrap() {
+                metrics::unpark();
+                t.unpark();
+            }
+        }
+
+        // 칸을 비운 뒤(또는 닫은 뒤)에 호출한다. 기다리던 sender 를 모두 깨워서 다시 시도하게 한다.
+        fn unpark_senders(&self) {
+            fence(SeqCst);
+            if self.num_blocked_senders.load(Relaxed) == 0 {
+                return;
+            }
+            let mut blocked = self.blocked_senders.lock().unwrap();
+            self.num_blocked_senders.store(0, Relaxed);
+            for t in blocked.drain(..) {
+                metrics::unpark();
+                t.unpark();
+            }
+        }
+    }
+
+    impl<T> Sender<T> {
+        pub fn send(&self, message: T) -> Result<(), T> {
+            self.channel.send(message)
+        }
+
+        pub fn send_slice(&self, items: &mut Vec<T>) -> usize {
+            self.channel.send_slice(items)
+        }
+
+        pub fn send_blocking(&self, message: T) -> Result<(), T> {
+            self.channel.send_blocking(message)
+        }
+
+        pub fn send_timeout(&self, message: T, timeout: Duration) -> Result<(), (T, ChannelError)> {
+            self.channel.send_timeout(message, timeout)
+        }
+
+        // drop 하지 않고 채널을 닫는다. 다른 Sender 도 더는 보낼 수 없고, 막혀 있던 receiver 는 깨어난다.
+        // Sender 는 남아 있으므로 is_closed 처럼 상태를 확인하는 데 계속 쓸 수 있다.
+        pub fn close(&self) {
+            self.channel.close();
+        }
+
+        pub fn is_closed(&self) -> bool {
+            self.channel.is_closed()
+        }
+    }
+
+    impl<T> Clone for Sender<T> {
+        fn clone(&self) -> Self {
+            self.channel.senders.fetch_add(1, Relaxed);
+            Sender { channel: self.channel.clone() }
+        }
+    }
+
+    impl<T> Drop for Sender<T> {
+        fn drop(&mut self) {
+            if self.channel.senders.fetch_sub(1, Release) == 1 {
+                self.channel.unpark_receiver();
+            }
+        }
+    }
+
+    impl<T> Receiver<T> {
+        pub fn recv(&self) -> Option<T> {
+            self.channel.recv()
+        }
+
+        pub fn recv_batch(&self, max: usize) -> Vec<T> {
+            self.channel.recv_batch(max)
+        }
+
+        // 기다리지 않는다. 닫히거나 Sender 가 모두 사라진 뒤 남은 메시지까지 다 받으면 Disconnected 를 돌려준다.
+        pub fn try_recv(&self) -> Result<T, ChannelError> {
+            if let Some(message) = self.channel.recv() {
+                return Ok(message);
+            }
+            if self.channel.is_disconnected() {
+                // 마지막 Sender 가 보낸 메시지가 남아 있을 수 있으므로 한 번 더 확인한다.
+                return self.channel.recv().ok_or(ChannelError::Disconnected);
+            }
+            Err(ChannelError::Empty)
+        }
+
+        // 메시지가 올 때까지 최대 timeout 동안 기다린다.
+        // 시간이 다 되면 TimedOut 을, 닫히거나 Sender 가 모두 사라져서 더 올 메시지가 없으면
+        // 시간이 남았어도 바로 Disconnected 를 돌려준다.
+        pub fn recv_timeout(&self, timeout: Duration) -> Result<T, ChannelError> {
+            let deadline = Instant::now() + timeout;
+            // send 가 이 스레드를 깨울 수 있도록, 확인하기 전에 등록한다.
+            *self.channel.receiving_thread.lock().unwrap() = Some(thread::current());
+            loop {
+                if let Some(message) = self.channel.recv() {
+                    return Ok(message);
+                }
+                if self.channel.is_disconnected() {
+                    return self.channel.recv().ok_or(ChannelError::Disconnected);
+                }
+                let now = Instant::now();
+                if now >= deadline {
+                    return Err(ChannelError::TimedOut);
+                }
+                metrics::park();
+                thread::park_timeout(deadline - now);
+            }
+        }
+    }
+
+    // 비어 있으면 기다리고, 닫히거나 모든 Sender 가 사라진 뒤 남은 메시지까지 다 받으면 None 을 돌려준다.
+    impl<T> Iterator for Receiver<T> {
+        type Item = T;
+
+        fn next(&mut self) -> Option<T> {
+            *self.channel.receiving_thread.lock().unwrap() = Some(thread::current());
+            loop {
+                if let Some(message) = self.channel.recv() {
+                    return Some(message);
+                }
+                // 마지막 Sender 가 보낸 메시지가 남아 있을 수 있으므로 한 번 더 확인한다.
+                if self.channel.is_disconnected() {
+                    return self.channel.recv();
+                }
+                metrics::park();
+                thread::park();
+            }
+        }
+    }
+
+    impl<T> Drop for Channel<T> {
+        fn drop(&mut self) {
+            // &mut self 이므로 진행 중인 send 는 없고, head..tail 의 자리는 모두 쓰여 있다.
+            // 받은 메시지는 recv 가 이미 꺼냈으므로 남은 칸만 정확히 한 번씩 drop 한다.
+            let capacity = self.capacity();
+            for i in *self.head.get_mut()..*self.tail.get_mut() {
+                let slot = *self.queue[i % capacity].get_mut();
+                debug_assert_ne!(slot, EMPTY);
+                unsafe { self.messages[slot].get_mut().assume_init_drop() }
+            }
+        }
+    }
+
+    pub fn run_bounded() {
+        let channel = Channel::new(4);
+        thread::scope(|s| {
+            for p in 0..2 {
+                let channel = &channel;
+                s.spawn(move || {
+                    for i in 0..10 {
+                        let mut message = p * 10 + i;
+                        while let Err(m) = channel.send(message) {
+                            message = m;
+                            thread::yield_now();
+                        }
+                    }
+                });
+            }
+
+            let mut received = 0;
+            while received < 20 {
+                match channel.recv() {
+                    Some(message) => {
+                        println!("received {message}");
+                        received += 1;
+                    }
+                    None => thread::yield_now(),
+                }
+            }
+        });
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::sync::Mutex;
+        use std::sync::atomic::Ordering::Relaxed;
+        use std::thread;
+        use std::time::{Duration, Instant};
+        use crate::ch5::channel::error::ChannelError;
+        use crate::ch5::drop_counter::DropCounter;
+        use super::{channel, Channel};
+
+        #[test]
+        fn close_drains_then_disconnects() {
+            let (sender, receiver) = channel(4);
+            sender.send(1).unwrap();
+            sender.send(2).unwrap();
+            assert_eq!(receiver.try_recv(), Ok(1));
+            sender.close();
+            assert!(sender.is_closed());
+            assert_eq!(sender.send(3), Err(3));
+            assert_eq!(sender.clone().send(4), Err(4));
+
+            assert_eq!(receiver.try_recv(), Ok(2));
+            assert_eq!(receiver.try_recv(), Err(ChannelError::Disconnected));
+        }
+
+        #[test]
+        fn close_wakes_blocked_receiver() {
+            let (sender, mut receiver) = channel::<u32>(4);
+            assert_eq!(receiver.try_recv(), Err(ChannelError::Empty));
+            thread::scope(|s| {
+                s.spawn(|| {
+                    thread::sleep(Duration::from_millis(20));
+                    sender.close();
+                });
+                assert_eq!(receiver.next(), None);
+            });
+        }
+
+        #[test]
+        fn close_while_sending_loses_nothing() {
+            let (sender, mut receiver) = channel(4);
+            let mut accepted = 0;
+            let mut received = 0;
+            thread::scope(|s| {
+                let t = s.spawn(|| {
+                    let sender = sender.clone();
+                    let mut accepted = 0;
+                    for i in 0..1000 {
+                        match sender.send(i) {
+                            Ok(()) => accepted += 1,
+                            Err(_) if sender.is_closed() => break,
+                            Err(_) => thread::yield_now(),
+                        }
+                    }
+                    accepted
+                });
+                s.spawn(|| {
+                    thread::sleep(Duration::from_millis(1));
+                    sender.close();
+                });
+                for _ in receiver.by_ref() {
+                    received += 1;
+                }
+                accepted = t.join().unwrap();
+            });
+            assert_eq!(received, accepted);
+        }
+
+        #[test]
+        fn remaining_messages_dropped_once_on_disconnect() {
+            let (counters, drops): (Vec<_>, Vec<_>) = (0..3).map(|_| DropCounter::new()).unzip();
+            let (sender, mut receiver) = channel(4);
+            for counter in counters {
+                assert!(sender.send(counter).is_ok());
+            }
+            drop(receiver.next());
+            drop(sender);
+            assert_eq!(drops.iter().map(|d| d.load(Relaxed)).collect::<Vec<_>>(), [1, 0, 0]);
+
+            drop(receiver);
+            assert_eq!(drops.iter().map(|d| d.load(Relaxed)).collect::<Vec<_>>(), [1, 1, 1]);
+        }
+
+        #[test]
+        fn iterate_until_disconnected() {
+            let (sender, receiver) = channel(8);
+            for i in 0..5 {
+                sender.send(i).unwrap();
+            }
+            drop(sender);
+            let received: Vec<_> = receiver.collect();
+            assert_eq!(received, [0, 1, 2, 3, 4]);
+        }
+
+        #[test]
+        fn iterate_blocks_while_connected() {
+            let (sender, receiver) = channel(2);
+            thread::scope(|s| {
+                s.spawn(move || {
+                    let other = sender.clone();
+                    for i in 0..100 {
+                        let sender = if i % 2 == 0 { &sender } else { &other };
+                        let mut message = i;
+                        while let Err(m) = sender.send(message) {
+                            message = m;
+                            thread::yield_now();
+                        }
+                    }
+                });
+                assert_eq!(receiver.collect::<Vec<_>>(), (0..100).collect::<Vec<_>>());
+            });
+        }
+
+        #[test]
+        fn full_and_empty() {
+            let channel = Channel::new(2);
+            assert_eq!(channel.recv(), None);
+            assert_eq!(channel.send(1), Ok(()));
+            assert_eq!(channel.send(2), Ok(()));
+            assert_eq!(channel.send(3), Err(3));
+            assert_eq!(channel.recv(), Some(1));
+            assert_eq!(channel.send(3), Ok(()));
+            assert_eq!(channel.recv(), Some(2));
+            assert_eq!(channel.recv(), Some(3));
+            assert_eq!(channel.recv(), None);
+        }
+
+        #[test]
+        fn batch_larger_than_capacity() {
+            let channel = Channel::new(4);
+            let mut items: Vec<_> = (0..6).collect();
+            assert_eq!(channel.send_slice(&mut items), 4);
+            assert_eq!(items, [4, 5]);
+            assert_eq!(channel.send_slice(&mut items), 0);
+
+            assert_eq!(channel.recv_batch(10), [0, 1, 2, 3]);
+            assert!(channel.is_empty());
+            assert_eq!(channel.send_slice(&mut items), 2);
+            assert!(items.is_empty());
+            assert_eq!(channel.recv_batch(1), [4]);
+            assert_eq!(channel.recv_batch(10), [5]);
+            assert_eq!(channel.recv_batch(10), []);
+        }
+
+        #[test]
+        fn send_blocking_waits_for_space() {
+            let channel = Channel::new(2);
+            let first_recv = Mutex::new(None);
+            let mut sent_at = Vec::new();
+            let mut received = Vec::new();
+            thread::scope(|s| {
+                s.spawn(|| {
+                    for _ in 0..5 {
+                        thread::sleep(Duration::from_millis(20));
+                        first_recv.lock().unwrap().get_or_insert_with(Instant::now);
+                        loop {
+                            if let Some(message) = channel.recv() {
+                                received.push(message);
+                                break;
+                            }
+                            thread::yield_now();
+                        }
+                    }
+                });
+                for i in 0..5 {
+                    channel.send_blocking(i).unwrap();
+                    sent_at.push(Instant::now());
+                }
+            });
+            assert_eq!(received, [0, 1, 2, 3, 4]);
+            // 앞의 두 개는 바로 들어가고, 세 번째는 첫 recv 가 칸을 비운 뒤에야 들어간다.
+            let first_recv = first_recv.into_inner().unwrap().unwrap();
+            assert!(sent_at[1] < first_recv);
+            assert!(sent_at[2] >= first_recv);
+        }
+
+        #[test]
+        fn close_wakes_blocked_sender() {
+            let (sender, receiver) = channel(1);
+            sender.send(1).unwrap();
+            thread::scope(|s| {
+                let t = s.spawn(|| sender.send_blocking(2));
+                thread::sleep(Duration::from_millis(20));
+                sender.close();
+                assert_eq!(t.join().unwrap(), Err(2));
+            });
+            assert_eq!(receiver.try_recv(), Ok(1));
+        }
+
+        #[test]
+        fn send_timeout_on_full_returns_message() {
+            let (sender, receiver) = channel(1);
+            sender.send(1).unwrap();
+            let start = Instant::now();
+            assert_eq!(sender.send_timeout(2, Duration::from_millis(20)), Err((2, ChannelError::TimedOut)));
+            assert!(start.elapsed() >= Duration::from_millis(20));
+            assert_eq!(receiver.try_recv(), Ok(1));
+        }
+
+        #[test]
+        fn send_timeout_on_closed_is_disconnected() {
+            let (sender, _receiver) = channel(1);
+            sender.close();
+            let start = Instant::now();
+            assert_eq!(sender.send_timeout(1, Duration::from_secs(10)), Err((1, ChannelError::Disconnected)));
+            assert!(start.elapsed() < Duration::from_secs(10));
+        }
+
+        #[test]
+        fn send_timeout_succeeds_when_space_frees() {
+            let (sender, receiver) = channel(1);
+            sender.send(1).unwrap();
+            thread::scope(|s| {
+                s.spawn(|| {
+                    thread::sleep(Duration::from_millis(10));
+                    assert_eq!(receiver.recv(), Some(1));
+                });
+                assert_eq!(sender.send_timeout(2, Duration::from_secs(10)), Ok(()));
+            });
+            assert_eq!(receiver.try_recv(), Ok(2));
+        }
+
+        #[test]
+        fn recv_timeout_on_empty() {
+            let (_sender, receiver) = channel::<i32>(1);
+            let start = Instant::now();
+            assert_eq!(receiver.recv_timeout(Duration::from_millis(20)), Err(ChannelError::TimedOut));
+            assert!(start.elapsed() >= Duration::from_millis(20));
+        }
+
+        #[test]
+        fn recv_timeout_receives() {
+            let (sender, receiver) = channel(1);
+            thread::scope(|s| {
+                s.spawn(move || {
+                    thread::sleep(Duration::from_millis(10));
+                    sender.send(1).unwrap();
+                });
+                assert_eq!(receiver.recv_timeout(Duration::from_secs(10)), Ok(1));
+            });
+            // Sender 가 사라졌으므로 기다리지 않는다.
+            let start = Instant::now();
+            assert_eq!(receiver.recv_timeout(Duration::from_secs(10)), Err(ChannelError::Disconnected));
+            assert!(start.elapsed() < Duration::from_secs(10));
+        }
+
+        #[test]
+        fn slots_recycled_under_churn() {
+            const CAPACITY: usize = 8;
+            let channel = Channel::new(CAPACITY);
+            let mut received = Vec::new();
+            thread::scope(|s| {
+                for p in 0..4 {
+                    let channel = &channel;
+                    s.spawn(move || {
+                        for i in 0..5000 {
+                            let mut message = p * 5000 + i;
+                            while let Err(m) = channel.send(message) {
+                                message = m;
+                                thread::yield_now();
+                            }
+                        }
+                    });
+                }
+                while received.len() < 20000 {
+                    assert!(channel.len() <= CAPACITY);
+                    match channel.recv_batch(3) {
+                        batch if batch.is_empty() => thread::yield_now(),
+                        batch => received.extend(batch),
+                    }
+                }
+            });
+
+            received.sort();
+            assert_eq!(received, (0..20000).collect::<Vec<_>>());
+            // 칸은 모두 free 에 돌아와 있고, capacity 개보다 많이 얻을 수 없다.
+            let slots: Vec<_> = (0..CAPACITY).map_while(|_| channel.free.acquire_slot()).collect();
+            assert_eq!(slots.len(), CAPACITY);
+            assert_eq!(channel.free.acquire_slot(), None);
+        }
+
+        #[test]
+        fn two_producers_one_consumer() {
+            let channel = Channel::new(4);
+            let mut received = Vec::new();
+            thread::scope(|s| {
+                for p in 0..2 {
+                    let channel = &channel;
+                    s.spawn(move || {
+                        for i in 0..1000 {
+                            let mut message = p * 1000 + i;
+                            while let Err(m) = channel.send(message) {
+                                message = m;
+                                thread::yield_now();
+                            }
+                        }
+                    });
+                }
+
+                while received.len() < 2000 {
+                    match channel.recv() {
+                        Some(message) => received.push(message),
+                        None => thread::yield_now(),
+                    }
+                }
+            });
+
+            received.sort();
+            assert_eq!(received, (0..2000).collect::<Vec<_>>());
+        }
+    }
+}
+
+pub mod broadcast {
+    use std::cell::UnsafeCell;
+    use std::mem::MaybeUninit;
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::{AtomicBool, AtomicU64};
+    use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+    use std::thread;
+    use std::thread::Thread;
+    use crate::ch5::channel::error::ChannelError;
+    use crate::ch5::channel::metrics;
+
+    // 하나의 메시지를 여러 receiver 가 각각 한 번씩 clone 해서 가져간다.
+    // consumed 의 i 번째 비트는 i 번째 receiver 가 이미 받았는지를 나타낸다.
+    struct Channel<T> {
+        message: UnsafeCell<MaybeUninit<T>>,
+        ready: AtomicBool,
+        consumed: AtomicU64,
+        // Sender 가 사라졌는지. 보내고 사라졌다면 ready 가 먼저 true 가 된다.
+        disconnected: AtomicBool,
+        // receive 에서 기다리는 스레드들. Sender 가 사라질 때 모두 깨우고 비운다.
+        waiting: Mutex<Vec<Thread>>,
+    }
+
+    pub struct Sender<T> {
+        channel: Arc<Channel<T>>,
+    }
+
+    pub struct Receiver<T> {
+        channel: Arc<Channel<T>>,
+        index: u32,
+    }
+
+    unsafe impl<T> Sync for Channel<T> where T: Send + Sync {}
+
+    pub fn channel<T: Clone>(receivers: u32) -> (Sender<T>, Vec<Receiver<T>>) {
+        assert!(receivers <= u64::BITS, "at most 64 receivers are supported");
+        let a = Arc::new(Channel {
+            message: UnsafeCell::new(MaybeUninit::uninit()),
+            ready: AtomicBool::new(false),
+            consumed: AtomicU64::new(0),
+            disconnected: AtomicBool::new(false),
+            waiting: Mutex::new(Vec::new()),
+        });
+        let receivers = (0..receivers)
+            .map(|index| Receiver { channel: a.clone(), index })
+            .collect();
+        (Sender { channel: a }, receivers)
+    }
+
+    impl<T> Sender<T> {
+        pub fn send(self, message: T) {
+            unsafe { (*self.channel.message.get()).write(message) };
+            self.channel.ready.store(true, Release);
+            // 기다리는 receiver 는 self 가 drop 될 때 깨운다.
+        }
+    }
+
+    impl<T> Drop for Sender<T> {
+        fn drop(&mut self) {
+            // send 의 ready store 다음에 오므로, disconnected 를 본 receiver 는 보낸 메시지도 본다.
+            self.channel.disconnected.store(true, Release);
+            for t in self.channel.waiting.lock().unwrap().drain(..) {
+                metrics::unpark();
+                t.unpark();
+            }
+        }
+    }
+
+    impl<T: Clone> Receiver<T> {
+        pub fn is_ready(&self) -> bool {
+            self.channel.ready.load(Relaxed)
+        }
+
+        // 메시지가 올 때까지 기다린다. 보내지 않은 채로 Sender 가 사라지면 Disconnected 를 돌려준다.
+        pub fn receive(&self) -> Result<T, ChannelError> {
+            let bit = 1 << self.index;
+            if self.channel.consumed.fetch_or(bit, Relaxed) & bit != 0 {
+                panic!("message already received!");
+            }
+
+            // ready 를 확인하기 전에 먼저 등록해야 Sender 가 drop 될 때의 unpark 를 놓치지 않는다.
+            self.channel.waiting.lock().unwrap().push(thread::current());
+            let ready = loop {
+                if self.channel.ready.load(Acquire) {
+                    break true;
+                }
+                if self.channel.disconnected.load(Acquire) {
+                    break self.channel.ready.load(Acquire);
+                }
+                metrics::park();
+                thread::park();
+            };
+            // 기다리지 않고 끝났다면 등록한 항목이 남아 있으므로 지운다.
+            let me = thread::current().id();
+            self.channel.waiting.lock().unwrap().retain(|t| t.id() != me);
+
+            if !ready {
+                return Err(ChannelError::Disconnected);
+            }
+            Ok(unsafe { (*self.channel.message.get()).assume_init_ref().clone() })
+        }
+    }
+
+    impl<T> Drop for Channel<T> {
+        fn drop(&mut self) {
+            if *self.ready.get_mut() {
+                unsafe { self.message.get_mut().assume_init_drop() }
+            }
+        }
+    }
+
+    pub fn run_broadcast() {
+        let (sender, receivers) = channel(3);
+        thread::scope(|s| {
+            for receiver in receivers {
+                s.spawn(move || {
+                    println!("receiver {}: {}", receiver.index, receiver.receive().unwrap());
+                });
+            }
+            s.spawn(move || {
+                sender.send(String::from("hello world!"));
+            });
+        });
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::thread;
+        use std::time::Duration;
+        use crate::ch5::channel::error::ChannelError;
+        use super::channel;
+
+        #[test]
+        fn all_receivers_observe_message() {
+            let (sender, receivers) = channel(3);
+            let received: Vec<String> = thread::scope(|s| {
+                let handles: Vec<_> = receivers
+                    .into_iter()
+                    .map(|receiver| s.spawn(move || receiver.receive().unwrap()))
+                    .collect();
+                s.spawn(move || {
+                    sender.send(String::from("hello world!"));
+                });
+                handles.into_iter().map(|h| h.join().unwrap()).collect()
+            });
+            assert_eq!(received, ["hello world!"; 3]);
+        }
+
+        #[test]
+        #[should_panic(expected = "message already received!")]
+        fn receive_twice_panics() {
+            let (sender, receivers) = channel(1);
+            sender.send(1);
+            assert_eq!(receivers[0].receive(), Ok(1));
+            let _ = receivers[0].receive();
+        }
+
+        #[test]
+        fn sender_drop_wakes_receivers() {
+            let (sender, receivers) = channel::<String>(3);
+            thread::scope(|s| {
+                let handles: Vec<_> = receivers
+                    .iter()
+                    .map(|receiver| s.spawn(move || receiver.receive()))
+                    .collect();
+                thread::sleep(Duration::from_millis(10));
+                drop(sender);
+                for h in handles {
+                    assert_eq!(h.join().unwrap(), Err(ChannelError::Disconnected));
+                }
+            });
+            // 깨어난 receiver 의 항목은 모두 지워졌다.
+            assert!(receivers[0].channel.waiting.lock().unwrap().is_empty());
+        }
+
+        #[test]
+        fn waiting_list_emptied_after_receive() {
+            let (sender, receivers) = channel(2);
+            sender.send(1);
+            assert_eq!(receivers[0].receive(), Ok(1));
+            assert_eq!(receivers[1].receive(), Ok(1));
+            assert!(receivers[0].channel.waiting.lock().unwrap().is_empty());
+        }
+    }
+}
+
+pub mod mpsc_oneshot {
+    use std::cell::UnsafeCell;
+    use std::mem::MaybeUninit;
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::{AtomicBool, AtomicUsize};
+    use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+    use std::thread;
+    use std::thread::Thread;
+    use crate::ch5::channel::error::ChannelError;
+    use crate::ch5::channel::metrics;
+
+    // 여러 Sender 중 처음으로 send 한 것만 성공하는 one-shot 채널.
+    struct Channel<T> {
+        message: UnsafeCell<MaybeUninit<T>>,
+        in_use: AtomicBool,
+        ready: AtomicBool,
+        // 살아 있는 Sender 의 수. 0 이 되면 receiver 를 깨워서 Disconnected 를 알린다.
+        senders: AtomicUsize,
+        receiving_thread: Mutex<Option<Thread>>,
+    }
+
+    pub struct Sender<T> {
+        channel: Arc<Channel<T>>,
+    }
+
+    pub struct Receiver<T> {
+        channel: Arc<Channel<T>>,
+    }
+
+    unsafe impl<T> Sync for Channel<T> where T: Send {}
+
+    pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+        let a = Arc::new(Channel {
+            message: UnsafeCell::new(MaybeUninit::uninit()),
+            in_use: AtomicBool::new(false),
+            ready: AtomicBool::new(false),
+            senders: AtomicUsize::new(1),
+            receiving_thread: Mutex::new(None),
+        });
+        (Sender { channel: a.clone() }, Receiver { channel: a })
+    }
+
+    impl<T> Channel<T> {
+        fn unpark_receiver(&self) {
+            if let Some(t) = &*self.receiving_thread.lock().unwrap() {
+                metrics::unpark();
+                t.unpark();
+            }
+        }
+    }
+
+    impl<T> Sender<T> {
+        pub fn send(&self, message: T) -> Result<(), T> {
+            if self.channel.in_use.swap(true, Relaxed) {
+                return Err(message);
+            }
+            unsafe { (*self.channel.message.get()).write(message) };
+            self.channel.ready.store(true, Release);
+            self.channel.unpark_receiver();
+            Ok(())
+        }
+    }
+
+    impl<T> Clone for Sender<T> {
+        fn clone(&self) -> Self {
+            self.channel.senders.fetch_add(1, Relaxed);
+            Sender { channel: self.channel.clone() }
+        }
+    }
+
+    impl<T> Drop for Sender<T> {
+        fn drop(&mut self) {
+            if self.channel.senders.fetch_sub(1, Release) == 1 {
+                self.channel.unpark_receiver();
+            }
+        }
+    }
+
+    impl<T> Receiver<T> {
+        pub fn is_ready(&self) -> bool {
+            self.channel.ready.load(Relaxed)
+        }
+
+        pub fn receive(self) -> Result<T, ChannelError> {
+            *self.channel.receiving_thread.lock().unwrap() = Some(thread::current());
+            loop {
+                if self.channel.ready.swap(false, Acquire) {
+                    return Ok(unsafe { (*self.channel.message.get()).assume_init_read() });
+                }
+                // 마지막 Sender 가 send 후에 drop 되었을 수도 있으므로 ready 를 한 번 더 확인한다.
+                if self.channel.senders.load(Acquire) == 0 {
+                    if self.channel.ready.swap(false, Acquire) {
+                        return Ok(unsafe { (*self.channel.message.get()).assume_init_read() });
+                    }
+                    return Err(ChannelError::Disconnected);
+                }
+                metrics::park();
+                thread::park();
+            }
+        }
+    }
+
+    impl<T> Drop for Channel<T> {
+        fn drop(&mut self) {
+            if *self.ready.get_mut() {
+                unsafe { self.message.get_mut().assume_init_drop() }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::thread;
+        use super::{channel, ChannelError};
+
+        #[test]
+        fn first_sender_wins() {
+            let (sender, receiver) = channel();
+            let other = sender.clone();
+            thread::scope(|s| {
+                s.spawn(move || {
+                    assert_eq!(other.send("hello world!"), Ok(()));
+                    assert_eq!(other.send("again"), Err("again"));
+                });
+                s.spawn(move || drop(sender));
+                assert_eq!(receiver.receive(), Ok("hello world!"));
+            });
+        }
+
+        #[test]
+        fn all_senders_dropped() {
+            let (sender, receiver) = channel::<u32>();
+            let other = sender.clone();
+            thread::scope(|s| {
+                s.spawn(move || drop(sender));
+                s.spawn(move || drop(other));
+                assert_eq!(receiver.receive(), Err(ChannelError::Disconnected));
+            });
+        }
+    }
+}
+
+pub mod blocking_queue {
+    use std::collections::VecDeque;
+    use crate::ch9::condvar::Condvar;
+    use crate::ch9::mutex::Mutex;
+
+    // 9장의 Mutex 와 Condvar 로 만든, 가득 차면 push 가 기다리는 bounded queue.
+    pub struct Queue<T> {
+        items: Mutex<VecDeque<T>>,
+        capacity: usize,
+        not_full: Condvar,
+        not_empty: Condvar,
+    }
+
+    impl<T> Queue<T> {
+        pub fn new(capacity: usize) -> Self {
+            assert!(capacity > 0, "capacity must be non-zero");
+            Self {
+                items: Mutex::new(VecDeque::with_capacity(capacity)),
+                capacity,
+                not_full: Condvar::new(),
+                not_empty: Condvar::new(),
+            }
+        }
+
+        pub fn push(&self, item: T) {
+            let mut items = self.items.lock().unwrap();
+            while items.len() == self.capacity {
+                items = self.not_full.wait(items).unwrap();
+            }
+            items.push_back(item);
+            drop(items);
+            self.not_empty.notify_one();
+        }
+
+        pub fn pop(&self) -> T {
+            let mut items = self.items.lock().unwrap();
+            loop {
+                if let Some(item) = items.pop_front() {
+                    drop(items);
+                    self.not_full.notify_one();
+                    return item;
+                }
+                items = self.not_empty.wait(items).unwrap();
+            }
+        }
+
+        pub fn len(&self) -> usize {
+            self.items.lock().unwrap().len()
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::thread;
+        use std::time::Duration;
+        use super::Queue;
+
+        #[test]
+        fn slow_consumer() {
+            let queue = Queue::new(3);
+            thread::scope(|s| {
+                s.spawn(|| {
+                    for i in 0..20 {
+                        queue.push(i);
+                        assert!(queue.len() <= 3);
+                    }
+                });
+
+                for i in 0..20 {
+                    thread::sleep(Duration::from_millis(2));
+                    assert!(queue.len() <= 3);
+                    assert_eq!(queue.pop(), i);
+                }
+            });
+            assert!(queue.is_empty());
+        }
+    }
+}
+
+pub mod mpmc {
+    use std::cell::UnsafeCell;
+    use std::mem::MaybeUninit;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+    use crate::ch5::channel::error::ChannelError;
+    use crate::ch5::channel::metrics;
+
+    // Dmitry Vyukov 의 bounded MPMC queue.
+    // 각 칸의 sequence 가 그 칸을 쓸 차례인지(== pos) 읽을 차례인지(== pos + 1) 를 나타낸다.
+    struct Slot<T> {
+        sequence: AtomicUsize,
+        message: UnsafeCell<MaybeUninit<T>>,
+    }
+
+    pub struct Channel<T> {
+        buffer: Box<[Slot<T>]>,
+        enqueue_pos: AtomicUsize,
+        dequeue_pos: AtomicUsize,
+    }
+
+    unsafe impl<T> Sync for Channel<T> where T: Send {}
+
+    // 채널이 가득 차서 보내지 못한 메시지를 돌려준다.
+    #[derive(Debug, PartialEq, Eq)]
+    pub struct Full<T>(pub T);
+
+    impl<T> Channel<T> {
+        pub fn new(capacity: usize) -> Self {
+            assert!(capacity > 0, "capacity must be non-zero");
+            Self {
+                buffer: (0..capacity)
+                    .map(|i| Slot {
+                        sequence: AtomicUsize::new(i),
+                        message: UnsafeCell::new(MaybeUninit::uninit()),
+                    })
+                    .collect(),
+                enqueue_pos: AtomicUsize::new(0),
+                dequeue_pos: AtomicUsize::new(0),
+            }
+        }
+
+        pub fn capacity(&self) -> usize {
+            self.buffer.len()
+        }
+
+        pub fn send(&self, message: T) -> Result<(), Full<T>> {
+            let mut pos = self.enqueue_pos.load(Relaxed);
+            loop {
+                let slot = &self.buffer[pos % self.capacity()];
+                // Acquire 로 이전 바퀴의 recv 가 칸을 비운 것(Release)을 본다.
+                let sequence = slot.sequence.load(Acquire);
+                let diff = sequence.wrapping_sub(pos) as isize;
+                if diff == 0 {
+                    // 쓸 차례인 칸이므로 enqueue_pos 를 올려서 이 칸을 차지한다.
+                    match self.enqueue_pos.compare_exchange_weak(pos, pos + 1, Relaxed, Relaxed) {
+                        Ok(_) => {
+                            unsafe { (*slot.message.get()).write(message) };
+                            slot.sequence.store(pos + 1, Release);
+                            metrics::send(true);
+                            return Ok(());
+                        }
+                        Err(p) => pos = p,
+                    }
+                } else if diff < 0 {
+                    // 한 바퀴 전의 메시지를 아직 아무도 꺼내 가지 않았다.
+                    metrics::send(false);
+                    return Err(Full(message));
+                } else {
+                    // 다른 sender 가 먼저 이 칸을 차지했다.
+                    pos = self.enqueue_pos.load(Relaxed);
+                }
+            }
+        }
+
+        pub fn recv(&self) -> Result<T, ChannelError> {
+            let mut pos = self.dequeue_pos.load(Relaxed);
+            loop {
+                let slot = &self.buffer[pos % self.capacity()];
+                // Acquire 로 send 가 칸에 쓴 것(Release)을 본다.
+                let sequence = slot.sequence.load(Acquire);
+                let diff = sequence.wrapping_sub(pos + 1) as isize;
+                if diff == 0 {
+                    match self.dequeue_pos.compare_exchange_weak(pos, pos + 1, Relaxed, Relaxed) {
+                        Ok(_) => {
+                            let message = unsafe { (*slot.message.get()).assume_init_read() };
+                            // 다음 바퀴의 send 가 쓸 수 있도록 한다.
+                            slot.sequence.store(pos + self.capacity(), Release);
+                            metrics::receive();
+                            return Ok(message);
+                        }
+                        Err(p) => pos = p,
+                    }
+                } else if diff < 0 {
+                    return Err(ChannelError::Empty);
+                } else {
+                    pos = self.dequeue_pos.load(Relaxed);
+                }
+            }
+        }
+    }
+
+    impl<T> Drop for Channel<T> {
+        fn drop(&mut self) {
+            while self.recv().is_ok() {}
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::sync::atomic::AtomicUsize;
+        use std::sync::atomic::Ordering::Relaxed;
+        use std::thread;
+        use crate::ch5::channel::error::ChannelError;
+        use crate::ch5::drop_counter::DropCounter;
+        use super::{Channel, Full};
+
+        #[test]
+        fn full_and_empty() {
+            let channel = Channel::new(2);
+            assert_eq!(channel.recv(), Err(ChannelError::Empty));
+            assert_eq!(channel.send(1), Ok(()));
+            assert_eq!(channel.send(2), Ok(()));
+            assert_eq!(channel.send(3), Err(Full(3)));
+            assert_eq!(channel.recv(), Ok(1));
+            assert_eq!(channel.send(3), Ok(()));
+            assert_eq!(channel.recv(), Ok(2));
+            assert_eq!(channel.recv(), Ok(3));
+            assert_eq!(channel.recv(), Err(ChannelError::Empty));
+        }
+
+        #[test]
+        fn four_producers_four_consumers() {
+            const PER_PRODUCER: usize = 2500;
+            let channel = Channel::new(16);
+            let received = AtomicUsize::new(0);
+
+            let mut all: Vec<usize> = thread::scope(|s| {
+                for p in 0..4 {
+                    let channel = &channel;
+                    s.spawn(move || {
+                        for mut i in p * PER_PRODUCER..(p + 1) * PER_PRODUCER {
+                            while let Err(Full(back)) = channel.send(i) {
+                                i = back;
+                                thread::yield_now();
+                            }
+                        }
+                    });
+                }
+
+                let consumers: Vec<_> = (0..4)
+                    .map(|_| {
+                        s.spawn(|| {
+                            let mut got = Vec::new();
+                            while received.load(Relaxed) < 4 * PER_PRODUCER {
+                                match channel.recv() {
+                                    Ok(i) => {
+                                        got.push(i);
+                                        received.fetch_add(1, Relaxed);
+                                    }
+                                    Err(_) => thread::yield_now(),
+                                }
+                            }
+                            got
+                        })
+                    })
+                    .collect();
+                consumers.into_iter().flat_map(|c| c.join().unwrap()).collect()
+            });
+
+            all.sort_unstable();
+            assert_eq!(all, (0..4 * PER_PRODUCER).collect::<Vec<_>>());
+        }
+
+        #[test]
+        fn drops_pending_messages() {
+            let (counter, drops) = DropCounter::new();
+            let channel = Channel::new(4);
+            assert!(channel.send(counter).is_ok());
+            drop(channel);
+            assert_eq!(drops.load(Relaxed), 1);
+        }
+    }
+}
+
+pub mod array {
+    use std::cell::UnsafeCell;
+    use std::marker::PhantomData;
+    use std::mem::MaybeUninit;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+
+    // 힙 할당 없이 메시지를 배열에 직접 담는 고정 크기 채널.
+    // 칸마다 ready 플래그가 없으므로 producer 와 consumer 는 하나씩만 있어야 하고, split 이 이를 보장한다.
+    pub struct ArrayChannel<T, const N: usize> {
+        buffer: [UnsafeCell<MaybeUninit<T>>; N],
+        // 다음에 읽을 칸. Receiver 만 바꾼다.
+        read: AtomicUsize,
+        // 다음에 쓸 칸. Sender 만 바꾼다.
+        write: AtomicUsize,
+        len: AtomicUsize,
+    }
+
+    unsafe impl<T, const N: usize> Sync for ArrayChannel<T, N> where T: Send {}
+
+    pub struct Sender<'a, T, const N: usize> {
+        channel: &'a ArrayChannel<T, N>,
+        _no_sync: PhantomData<*const ()>,
+    }
+
+    pub struct Receiver<'a, T, const N: usize> {
+        channel: &'a ArrayChannel<T, N>,
+        _no_sync: PhantomData<*const ()>,
+    }
+
+    unsafe impl<T: Send, const N: usize> Send for Sender<'_, T, N> {}
+    unsafe impl<T: Send, const N: usize> Send for Receiver<'_, T, N> {}
+
+    impl<T, const N: usize> ArrayChannel<T, N> {
+        pub const fn new() -> Self {
+            assert!(N > 0, "capacity must be non-zero");
+            Self {
+                buffer: [const { UnsafeCell::new(MaybeUninit::uninit()) }; N],
+                read: AtomicUsize::new(0),
+                write: AtomicUsize::new(0),
+                len: AtomicUsize::new(0),
+            }
+        }
+
+        pub fn split(&mut self) -> (Sender<'_, T, N>, Receiver<'_, T, N>) {
+            // 이전에 남은 메시지를 버리고 처음 상태로 되돌린다.
+            *self = Self::new();
+            (
+                Sender { channel: self, _no_sync: PhantomData },
+                Receiver { channel: self, _no_sync: PhantomData },
+            )
+        }
+
+        pub fn capacity(&self) -> usize {
+            N
+        }
+
+        pub fn len(&self) -> usize {
+            self.len.load(Relaxed)
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.len() == 0
+        }
+    }
+
+    impl<T, const N: usize> Default for ArrayChannel<T, N> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<T, const N: usize> Sender<'_, T, N> {
+        pub fn send(&mut self, message: T) -> Result<(), T> {
+            // Acquire 로 recv 가 칸을 비운 것(Release)을 본 뒤에만 그 칸에 쓴다.
+            if self.channel.len.load(Acquire) == N {
+                return Err(message);
+            }
+            let write = self.channel.write.load(Relaxed);
+            unsafe { (*self.channel.buffer[write].get()).write(message) };
+            self.channel.write.store((write + 1) % N, Relaxed);
+            self.channel.len.fetch_add(1, Release);
+            Ok(())
+        }
+    }
+
+    impl<T, const N: usize> Receiver<'_, T, N> {
+        pub fn recv(&mut self) -> Option<T> {
+            // Acquire 로 send 가 칸에 쓴 것(Release)을 본 뒤에만 그 칸을 읽는다.
+            if self.channel.len.load(Acquire) == 0 {
+                return None;
+            }
+            let read = self.channel.read.load(Relaxed);
+            let message = unsafe { (*self.channel.buffer[read].get()).assume_init_read() };
+            self.channel.read.store((read + 1) % N, Relaxed);
+            self.channel.len.fetch_sub(1, Release);
+            Some(message)
+        }
+    }
+
+    impl<T, const N: usize> Drop for ArrayChannel<T, N> {
+        fn drop(&mut self) {
+            let read = *self.read.get_mut();
+            for i in 0..*self.len.get_mut() {
+                unsafe { self.buffer[(read + i) % N].get_mut().assume_init_drop() };
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::sync::atomic::Ordering::Relaxed;
+        use std::thread;
+        use super::ArrayChannel;
+        use crate::ch5::drop_counter::DropCounter;
+
+        #[test]
+        fn fill_and_drain() {
+            let mut channel = ArrayChannel::<u32, 4>::new();
+            let (mut sender, mut receiver) = channel.split();
+            for i in 0..4 {
+                assert_eq!(sender.send(i), Ok(()));
+            }
+            assert_eq!(sender.send(4), Err(4));
+            for i in 0..4 {
+                assert_eq!(receiver.recv(), Some(i));
+            }
+            assert_eq!(receiver.recv(), None);
+        }
+
+        #[test]
+        fn wraps_around_across_threads() {
+            let mut channel = ArrayChannel::<u32, 4>::new();
+            let (mut sender, mut receiver) = channel.split();
+            thread::scope(|s| {
+                s.spawn(move || {
+                    for mut i in 0..100 {
+                        while let Err(back) = sender.send(i) {
+                            i = back;
+                            thread::yield_now();
+                        }
+                    }
+                });
+
+                for i in 0..100 {
+                    loop {
+                        if let Some(message) = receiver.recv() {
+                            assert_eq!(message, i);
+                            break;
+                        }
+                        thread::yield_now();
+                    }
+                }
+            });
+        }
+
+        #[test]
+        fn drops_pending_messages() {
+            let (counter, drops) = DropCounter::new();
+            let mut channel = ArrayChannel::<DropCounter, 2>::new();
+            let (mut sender, _) = channel.split();
+            assert!(sender.send(counter).is_ok());
+            drop(channel);
+            assert_eq!(drops.load(Relaxed), 1);
+        }
+    }
+}
+
+pub mod spsc {
+    use std::cell::UnsafeCell;
+    use std::marker::PhantomData;
+    use std::mem::MaybeUninit;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::{Acquire, Release};
+
+    // producer 하나와 consumer 하나만을 위한 wait-free 링 버퍼.
+    // tail 은 producer 만, head 는 consumer 만 쓰고, 둘 다 계속 증가하며 N 으로 나눈 나머지가 칸의 위치다.
+    pub struct Queue<T, const N: usize> {
+        buffer: [UnsafeCell<MaybeUninit<T>>; N],
+        head: AtomicUsize,
+        tail: AtomicUsize,
+    }
+
+    unsafe impl<T, const N: usize> Sync for Queue<T, N> where T: Send {}
+
+    // 상대방의 인덱스를 캐시해 두고, 캐시만으로 판단할 수 없을 때만 atomic 을 다시 읽는다.
+    pub struct Producer<'a, T, const N: usize> {
+        queue: &'a Queue<T, N>,
+        tail: usize,
+        cached_head: usize,
+        _no_sync: PhantomData<*const ()>,
+    }
+
+    pub struct Consumer<'a, T, const N: usize> {
+        queue: &'a Queue<T, N>,
+        head: usize,
+        cached_tail: usize,
+        _no_sync: PhantomData<*const ()>,
+    }
+
+    unsafe impl<T: Send, const N: usize> Send for Producer<'_, T, N> {}
+    unsafe impl<T: Send, const N: usize> Send for Consumer<'_, T, N> {}
+
+    impl<T, const N: usize> Queue<T, N> {
+        pub const fn new() -> Self {
+            assert!(N > 0, "capacity must be non-zero");
+            Self {
+                buffer: [const { UnsafeCell::new(MaybeUninit::uninit()) }; N],
+                head: AtomicUsize::new(0),
+                tail: AtomicUsize::new(0),
+            }
+        }
+
+        pub fn split(&mut self) -> (Producer<'_, T, N>, Consumer<'_, T, N>) {
+            // 이전에 남은 메시지를 버리고 처음 상태로 되돌린다.
+            *self = Self::new();
+            (
+                Producer { queue: self, tail: 0, cached_head: 0, _no_sync: PhantomData },
+                Consumer { queue: self, head: 0, cached_tail: 0, _no_sync: PhantomData },
+            )
+        }
+    }
+
+    impl<T, const N: usize> Default for Queue<T, N> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<T, const N: usize> Producer<'_, T, N> {
+        pub fn push(&mut self, message: T) -> Result<(), T> {
+            if self.tail - self.cached_head == N {
+                // Acquire 로 consumer 가 칸을 비운 것(Release)을 본 뒤에만 그 칸에 쓴다.
+                self.cached_head = self.queue.head.load(Acquire);
+                if self.tail - self.cached_head == N {
+                    return Err(message);
+                }
+            }
+            unsafe { (*self.queue.buffer[self.tail % N].get()).write(message) };
+            self.tail += 1;
+            self.queue.tail.store(self.tail, Release);
+            Ok(())
+        }
+    }
+
+    impl<T, const N: usize> Consumer<'_, T, N> {
+        pub fn pop(&mut self) -> Option<T> {
+            if self.head == self.cached_tail {
+                // Acquire 로 producer 가 칸에 쓴 것(Release)을 본 뒤에만 그 칸을 읽는다.
+                self.cached_tail = self.queue.tail.load(Acquire);
+                if self.head == self.cached_tail {
+                    return None;
+                }
+            }
+            let message = unsafe { (*self.queue.buffer[self.head % N].get()).assume_init_read() };
+            self.head += 1;
+            self.queue.head.store(self.head, Release);
+            Some(message)
+        }
+    }
+
+    impl<T, const N: usize> Drop for Queue<T, N> {
+        fn drop(&mut self) {
+            for i in *self.head.get_mut()..*self.tail.get_mut() {
+                unsafe { self.buffer[i % N].get_mut().assume_init_drop() };
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::sync::atomic::Ordering::Relaxed;
+        use std::thread;
+        use crate::ch5::drop_counter::DropCounter;
+        use super::Queue;
+
+        #[test]
+        fn transfer_sum() {
+            const COUNT: u64 = 100_000;
+            let mut queue = Queue::<u64, 64>::new();
+            let (mut producer, mut consumer) = queue.split();
+
+            let sum = thread::scope(|s| {
+                s.spawn(move || {
+                    for mut i in 1..=COUNT {
+                        while let Err(back) = producer.push(i) {
+                            i = back;
+                            thread::yield_now();
+                        }
+                    }
+                });
+
+                let mut sum = 0;
+                let mut received = 0;
+                while received < COUNT {
+                    match consumer.pop() {
+                        Some(i) => {
+                            sum += i;
+                            received += 1;
+                        }
+                        None => thread::yield_now(),
+                    }
+                }
+                sum
+            });
+
+            assert_eq!(sum, COUNT * (COUNT + 1) / 2);
+        }
+
+        #[test]
+        fn drops_pending_messages() {
+            let (counter, drops) = DropCounter::new();
+            let mut queue = Queue::<DropCounter, 2>::new();
+            let (mut producer, _) = queue.split();
+            assert!(producer.push(counter).is_ok());
+            drop(queue);
+            assert_eq!(drops.load(Relaxed), 1);
+        }
+    }
+}
+
+pub mod rendezvous {
+    use std::cell::UnsafeCell;
+    use std::marker::PhantomData;
+    use std::mem::MaybeUninit;
+    use std::sync::atomic::AtomicU32;
+    use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+    use crate::ch9::futex::{wait, wake_one};
+
+    const EMPTY: u32 = 0;
+    const FULL: u32 = 1;
+
+    // 버퍼가 없는 채널: send 는 receiver 가 값을 가져갈 때까지, recv 는 sender 가 값을 줄 때까지 기다린다.
+    // receiver 는 state 가 FULL 이 되기를, sender 는 taken 이 늘어나기를 futex 로 기다린다.
+    pub struct Channel<T> {
+        message: UnsafeCell<MaybeUninit<T>>,
+        state: AtomicU32,
+        // 지금까지 넘겨준 메시지 수. sender 는 자기 메시지가 가져가질 때까지 이 값을 기다린다.
+        taken: AtomicU32,
+    }
+
+    unsafe impl<T> Sync for Channel<T> where T: Send {}
+
+    pub struct Sender<'a, T> {
+        channel: &'a Channel<T>,
+        _no_sync: PhantomData<*const ()>,
+    }
+
+    pub struct Receiver<'a, T> {
+        channel: &'a Channel<T>,
+        _no_sync: PhantomData<*const ()>,
+    }
+
+    unsafe impl<T: Send> Send for Sender<'_, T> {}
+    unsafe impl<T: Send> Send for Receiver<'_, T> {}
+
+    impl<T> Channel<T> {
+        pub const fn new() -> Self {
+            Self {
+                message: UnsafeCell::new(MaybeUninit::uninit()),
+                state: AtomicU32::new(EMPTY),
+                taken: AtomicU32::new(0),
+            }
+        }
+
+        pub fn split(&mut self) -> (Sender<'_, T>, Receiver<'_, T>) {
+            *self = Self::new();
+            (
+                Sender { channel: self, _no_sync: PhantomData },
+                Receiver { channel: self, _no_sync: PhantomData },
+            )
+        }
+    }
+
+    impl<T> Default for Channel<T> {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    impl<T> Sender<'_, T> {
+        // receiver 가 이 메시지를 가져간 뒤에야 돌아온다.
+        // receiver 가 recv 하지 않고 사라지면 영원히 기다린다.
+        pub fn send(&mut self, message: T) {
+            let c = self.channel;
+            let taken = c.taken.load(Relaxed);
+            unsafe { (*c.message.get()).write(message) };
+            c.state.store(FULL, Release);
+            wake_one(&c.state);
+
+            // Acquire: receiver 가 메시지를 다 읽은 뒤에야 다음 send 가 칸에 쓴다.
+            loop {
+                let now = c.taken.load(Acquire);
+                if now != taken {
+                    break;
+                }
+                wait(&c.taken, now);
+            }
+        }
+    }
+
+    impl<T> Receiver<'_, T> {
+        pub fn recv(&mut self) -> T {
+            let c = self.channel;
+            while c.state.load(Acquire) != FULL {
+                wait(&c.state, EMPTY);
+            }
+            let message = unsafe { (*c.message.get()).assume_init_read() };
+            c.state.store(EMPTY, Relaxed);
+            c.taken.fetch_add(1, Release);
+            wake_one(&c.taken);
+            message
+        }
+    }
+
+    impl<T> Drop for Channel<T> {
+        fn drop(&mut self) {
+            // send 는 메시지를 가져갈 때까지 돌아오지 않으므로 보통은 비어 있다.
+            if *self.state.get_mut() == FULL {
+                unsafe { self.message.get_mut().assume_init_drop() }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::thread;
+        use std::time::{Duration, Instant};
+        use super::Channel;
+
+        #[test]
+        fn send_returns_after_recv_started() {
+            let mut channel = Channel::new();
+            let (mut sender, mut receiver) = channel.split();
+            thread::scope(|s| {
+                let (started, wait_for_started) = std::sync::mpsc::channel();
+                let sent = s.spawn(move || {
+                    started.send(()).unwrap();
+                    sender.send(String::from("hello world!"));
+                    Instant::now()
+                });
+
+                // sender 가 send 에 들어갈 시간을 준다. 늦게 들어가더라도 아래의 순서 보장은 그대로다.
+                wait_for_started.recv().unwrap();
+                thread::sleep(Duration::from_millis(20));
+                let recv_started = Instant::now();
+                assert_eq!(receiver.recv(), "hello world!");
+
+                // rendezvous 가 보장하는 것은 send 가 recv 보다 먼저 끝나지 않는다는 것뿐이다.
+                let after = sent.join().unwrap();
+                assert!(after >= recv_started);
+            });
+        }
+
+        #[test]
+        fn many_handoffs() {
+            let mut channel = Channel::new();
+            let (mut sender, mut receiver) = channel.split();
+            thread::scope(|s| {
+                s.spawn(move || {
+                    for i in 0..1000 {
+                        sender.send(i);
+                    }
+                });
+                for i in 0..1000 {
+                    assert_eq!(receiver.recv(), i);
+                }
+            });
+        }
+    }
+}
+
+// `cargo test --release bench -- --ignored --nocapture` 로 실행한다.
+// ping 을 보내고 pong 을 받는 한 번의 왕복을 ch5_6 채널과 std::sync::mpsc 로 비교한다.
+// 상대 스레드는 시간을 재기 전에 띄워 두므로 스레드 생성 비용은 들어가지 않는다.
+#[cfg(test)]
+mod bench {
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use crate::ch5::channel::ch5_6;
+
+    const WARMUP: usize = 1_000;
+    const ITERATIONS: usize = 10_000;
+
+    // ch5_6 채널은 한 번만 쓸 수 있으므로 왕복마다 쓸 채널을 미리 모두 split 해 둔다.
+    fn ch5_6_round_trip() -> Duration {
+        let total = WARMUP + ITERATIONS;
+        let mut pings: Vec<ch5_6::Channel<usize>> = (0..total).map(|_| ch5_6::Channel::new()).collect();
+        let mut pongs: Vec<ch5_6::Channel<usize>> = (0..total).map(|_| ch5_6::Channel::new()).collect();
+        let (ping_senders, ping_receivers): (Vec<_>, Vec<_>) = pings.iter_mut().map(|c| c.split()).unzip();
+        let (pong_senders, pong_receivers): (Vec<_>, Vec<_>) = pongs.iter_mut().map(|c| c.split()).unzip();
+
+        thread::scope(|s| {
+            s.spawn(move || {
+                for (receiver, sender) in ping_receivers.into_iter().zip(pong_senders) {
+                    sender.send(receiver.receive().unwrap());
+                }
+            });
+            let mut start = Instant::now();
+            for (i, (sender, receiver)) in ping_senders.into_iter().zip(pong_receivers).enumerate() {
+                if i == WARMUP {
+                    start = Instant::now();
+                }
+                sender.send(i);
+                assert_eq!(receiver.receive(), Ok(i));
+            }
+            start.elapsed() / ITERATIONS as u32
+        })
+    }
+
+    fn std_round_trip() -> Duration {
+        let (ping_sender, ping_receiver) = mpsc::channel();
+        let (pong_sender, pong_receiver) = mpsc::channel();
+
+        thread::scope(|s| {
+            s.spawn(move || {
+                for message in ping_receiver {
+                    pong_sender.send(message).unwrap();
+                }
+            });
+            let mut start = Instant::now();
+            for i in 0..WARMUP + ITERATIONS {
+                if i == WARMUP {
+                    start = Instant::now();
+                }
+                ping_sender.send(i).unwrap();
+                assert_eq!(pong_receiver.recv(), Ok(i));
+            }
+            let elapsed = start.elapsed() / ITERATIONS as u32;
+            // 상대 스레드의 for 문이 끝나도록 ping 채널을 닫는다.
+            drop(ping_sender);
+            elapsed
+        })
+    }
+
+    #[test]
+    #[ignore]
+    fn ch5_6_vs_std_mpsc() {
+        println!("ch5_6:     {} ns/round trip", ch5_6_round_trip().as_nanos());
+        println!("std mpsc:  {} ns/round trip", std_round_trip().as_nanos());
+    }
+}
+
+pub mod priority {
+    use std::sync::Mutex;
+    use std::sync::atomic::{fence, AtomicBool};
+    use std::sync::atomic::Ordering::{Relaxed, SeqCst};
+    use std::thread;
+    use std::thread::Thread;
+    use crate::ch5::channel::bounded;
+    use crate::ch5::channel::metrics;
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum Priority {
+        High,
+        Low,
+    }
+
+    // 우선순위마다 bounded 채널을 하나씩 두고, recv 는 항상 high 를 먼저 비운다.
+    // receiver 는 하나라고 가정한다. 둘 다 비어 있으면 waiting 을 켜고 park 한다.
+    pub struct Channel<T> {
+        high: bounded::Channel<T>,
+        low: bounded::Channel<T>,
+        waiting: AtomicBool,
+        receiving_thread: Mutex<Option<Thread>>,
+    }
+
+    impl<T> Channel<T> {
+        pub fn new(capacity: usize) -> Self {
+            Self {
+                high: bounded::Channel::new(capacity),
+                low: bounded::Channel::new(capacity),
+                waiting: AtomicBool::new(false),
+                receiving_thread: Mutex::new(None),
+            }
+        }
+
+        // 해당 우선순위의 버퍼가 가득 차 있으면 메시지를 돌려준다.
+        pub fn send(&self, message: T, priority: Priority) -> Result<(), T> {
+            match priority {
+                Priority::High => self.high.send(message)?,
+                Priority::Low => self.low.send(message)?,
+            }
+            // recv 쪽 fence 와 짝을 이룬다. receiver 가 메시지를 못 보고 잠들려 한다면
+            // 여기서는 반드시 waiting 이 켜진 것을 본다.
+            fence(SeqCst);
+            if self.waiting.load(Relaxed) {
+                if let Some(t) = &*self.receiving_thread.lock().unwrap() {
+                    metrics::unpark();
+                    t.unpark();
+                }
+            }
+            Ok(())
+        }
+
+        pub fn try_recv(&self) -> Option<T> {
+            self.high.recv().or_else(|| self.low.recv())
+        }
+
+        pub fn recv(&self) -> T {
+            if let Some(message) = self.try_recv() {
+                return message;
+            }
+            *self.receiving_thread.lock().unwrap() = Some(thread::current());
+            loop {
+                self.waiting.store(true, Relaxed);
+                fence(SeqCst);
+                // waiting 을 켠 뒤에 다시 확인해야 그 사이에 온 메시지를 놓치지 않는다.
+                if let Some(message) = self.try_recv() {
+                    self.waiting.store(false, Relaxed);
+                    return message;
+                }
+                metrics::park();
+                thread::park();
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::thread;
+        use std::time::Duration;
+        use super::{Channel, Priority};
+
+        #[test]
+        fn high_before_low() {
+            let channel = Channel::new(8);
+            for i in 0..4 {
+                channel.send(100 + i, Priority::Low).unwrap();
+                channel.send(i, Priority::High).unwrap();
+            }
+            let received: Vec<_> = (0..8).map(|_| channel.recv()).collect();
+            assert_eq!(received, [0, 1, 2, 3, 100, 101, 102, 103]);
+            assert_eq!(channel.try_recv(), None);
+        }
+
+        #[test]
+        fn recv_blocks_until_sent() {
+            let channel = Channel::new(4);
+            thread::scope(|s| {
+                s.spawn(|| {
+                    thread::sleep(Duration::from_millis(20));
+                    channel.send(1, Priority::Low).unwrap();
+                });
+                assert_eq!(channel.recv(), 1);
+            });
+        }
+
+        #[test]
+        fn many_messages_across_threads() {
+            let channel = Channel::new(4);
+            let mut received = Vec::new();
+            thread::scope(|s| {
+                for priority in [Priority::High, Priority::Low] {
+                    let channel = &channel;
+                    s.spawn(move || {
+                        for i in 0..1000 {
+                            let mut message = i;
+                            while let Err(m) = channel.send(message, priority) {
+                                message = m;
+                                thread::yield_now();
+                            }
+                        }
+                    });
+                }
+                while received.len() < 2000 {
+                    received.push(channel.recv());
+                }
+            });
+            received.sort();
+            assert_eq!(received, (0..1000).flat_map(|i| [i, i]).collect::<Vec<_>>());
+        }
+    }
+}
+
+pub mod registry {
+    use std::any::Any;
+    use std::collections::HashMap;
+    use crate::ch5::channel::bounded::Sender;
+    use crate::ch9::rwlock::RwLock;
+
+    // 이름으로 bounded 채널의 Sender 를 찾는 표.
+    // 값의 타입은 지워서 저장하고, get 할 때 Any 가 TypeId 를 비교해서 요청한 타입이 맞을 때만 돌려준다.
+    // 찾는 일이 등록보다 훨씬 잦으므로 RwLock 을 쓴다.
+    pub struct Registry {
+        senders: RwLock<HashMap<String, Box<dyn Any + Send + Sync>>>,
+    }
+
+    impl Registry {
+        pub fn new() -> Self {
+            Self { senders: RwLock::new(HashMap::new()) }
+        }
+
+        // 같은 이름으로 이미 등록된 Sender 가 있으면 바꾼다.
+        pub fn register<T: Send + 'static>(&self, key: impl Into<String>, sender: Sender<T>) {
+            self.senders.write().insert(key.into(), Box::new(sender));
+        }
+
+        // 등록된 것이 없거나 타입이 다르면 None
+        pub fn get<T: Send + 'static>(&self, key: &str) -> Option<Sender<T>> {
+            self.senders.read().get(key)?.downcast_ref::<Sender<T>>().cloned()
+        }
+
+        pub fn unregister(&self, key: &str) -> bool {
+            self.senders.write().remove(key).is_some()
+        }
+    }
+
+    impl Default for Registry {
+        fn default() -> Self {
+            Self::new()
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::thread;
+        use crate::ch5::channel::bounded::channel;
+        use super::Registry;
+
+        #[test]
+        fn get_checks_type() {
+            let registry = Registry::new();
+            let (sender, receiver) = channel::<u32>(4);
+            registry.register("numbers", sender);
+
+            assert!(registry.get::<String>("numbers").is_none());
+            assert!(registry.get::<u32>("missing").is_none());
+
+            thread::scope(|s| {
+                s.spawn(|| registry.get::<u32>("numbers").unwrap().send(7).unwrap());
+            });
+            assert_eq!(receiver.recv(), Some(7));
+
+            assert!(registry.unregister("numbers"));
+            assert!(registry.get::<u32>("numbers").is_none());
+        }
+    }
+}
+
+pub mod state_machine {
+    use std::cell::UnsafeCell;
+    use std::mem::MaybeUninit;
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicU8;
+    use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+    use crate::ch5::channel::error::ChannelError;
+    use crate::ch5::channel::parker::{Parker, Unparker};
+    use crate::ch5::channel::metrics;
+
+    // 상태 하나로 "아직 안 보냄", "보내지 않고 사라짐", "이미 받음" 을 구분하는 oneshot 채널.
+    // 모든 전이는 compare_exchange 로 하므로, 기대한 상태가 아니면 전이하지 않는다.
+    //
+    // EMPTY -> SENDING -> READY -> RECEIVED
+    //   \
+    //    -> DISCONNECTED (send 하지 않고 Sender 가 drop 됨)
+    const EMPTY: u8 = 0;
+    // sender 가 메시지를 쓰는 중. receiver 에게는 EMPTY 와 같다.
+    const SENDING: u8 = 1;
+    const READY: u8 = 2;
+    const RECEIVED: u8 = 3;
+    const DISCONNECTED: u8 = 4;
+
+    struct Channel<T> {
+        message: UnsafeCell<MaybeUninit<T>>,
+        state: AtomicU8,
+        unparker: Unparker,
+    }
+
+    unsafe impl<T> Sync for Channel<T> where T: Send {}
+
+    pub struct Sender<T> {
+        channel: Arc<Channel<T>>,
+    }
+
+    // receive 가 self 를 빌리기만 하므로 두 번째 receive 는 AlreadyReceived 를 돌려받는다.
+    pub struct Receiver<T> {
+        channel: Arc<Channel<T>>,
+        parker: Parker,
+    }
+
+    pub fn channel<T>() -> (Sender<T>, Receiver<T>) {
+        let parker = Parker::new();
+        let a = Arc::new(Channel {
+            message: UnsafeCell::new(MaybeUninit::uninit()),
+            state: AtomicU8::new(EMPTY),
+            unparker: parker.unparker(),
+        });
+        (Sender { channel: a.clone() }, Receiver { channel: a, parker })
+    }
+
+    impl<T> Sender<T> {
+        pub fn send(self, message: T) {
+            // Sender 는 하나이고 send 는 self 를 소비하므로, 여기서 상태는 항상 EMPTY 다.
+            let claimed = self.channel.state.compare_exchange(EMPTY, SENDING, Relaxed, Relaxed);
+            assert_eq!(claimed, Ok(EMPTY), "send on a channel that is not empty");
+            unsafe { (*self.channel.message.get()).write(message) };
+            // Release: 메시지를 쓴 것이 READY 를 Acquire 로 본 receiver 에게 보이게 한다.
+            let published = self.channel.state.compare_exchange(SENDING, READY, Release, Relaxed);
+            debug_assert_eq!(published, Ok(SENDING));
+            // drop 이 깨워 준다.
+        }
+    }
+
+    impl<T> Drop for Sender<T> {
+        fn drop(&mut self) {
+            // 보내지 않은 채로 사라질 때만 DISCONNECTED 가 된다. 보냈다면 상태는 이미 READY 이후다.
+            let _ = self.channel.state.compare_exchange(EMPTY, DISCONNECTED, Release, Relaxed);
+            metrics::unpark();
+            self.channel.unparker.unpark();
+        }
+    }
+
+    impl<T> Receiver<T> {
+        // 기다리지 않는다. 아직 보내지 않았으면 Empty.
+        pub fn try_receive(&self) -> Result<T, ChannelError> {
+            // Acquire: READY 를 보면 sender 가 쓴 메시지도 보인다.
+            match self.channel.state.compare_exchange(READY, RECEIVED, Acquire, Acquire) {
+                Ok(_) => Ok(unsafe { (*self.channel.message.get()).assume_init_read() }),
+                Err(EMPTY | SENDING) => Err(ChannelError::Empty),
+                Err(RECEIVED) => Err(ChannelError::AlreadyReceived),
+                Err(DISCONNECTED) => Err(ChannelError::Disconnected),
+                Err(state) => unreachable!("invalid channel state {state}"),
+            }
+        }
+
+        // 보내거나 sender 가 사라질 때까지 기다린다.
+        pub fn receive(&self) -> Result<T, ChannelError> {
+            loop {
+                match self.try_receive() {
+                    Err(ChannelError::Empty) => {
+                        metrics::park();
+                        self.parker.park();
+                    }
+                    result => return result,
+                }
+            }
+        }
+    }
+
+    impl<T> Drop for Channel<T> {
+        fn drop(&mut self) {
+            if *self.state.get_mut() == READY {
+                unsafe { self.message.get_mut().assume_init_drop() }
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use std::sync::atomic::Ordering::Relaxed;
+        use std::thread;
+        use std::time::Duration;
+        use crate::ch5::channel::error::ChannelError;
+        use crate::ch5::drop_counter::DropCounter;
+        use super::channel;
+
+        #[test]
+        fn received_then_already_received() {
+            let (sender, receiver) = channel();
+            assert_eq!(receiver.try_receive(), Err(ChannelError::Empty));
+            sender.send(String::from("hello"));
+            assert_eq!(receiver.receive().as_deref(), Ok("hello"));
+            assert_eq!(receiver.receive(), Err(ChannelError::AlreadyReceived));
+            assert_eq!(receiver.try_receive(), Err(ChannelError::AlreadyReceived));
+        }
+
+        #[test]
+        fn dropped_without_send_is_disconnected() {
+            let (sender, receiver) = channel::<u32>();
+            thread::scope(|s| {
+                s.spawn(move || {
+                    thread::sleep(Duration::from_millis(20));
+                    drop(sender);
+                });
+                assert_eq!(receiver.receive(), Err(ChannelError::Disconnected));
+            });
+            assert_eq!(receiver.try_receive(), Err(ChannelError::Disconnected));
+        }
+
+        #[test]
+        fn receive_waits_for_send() {
+            let (sender, receiver) = channel();
+            thread::scope(|s| {
+                s.spawn(move || {
+                    thread::sleep(Duration::from_millis(20));
+                    sender.send(123);
+                });
+                assert_eq!(receiver.receive(), Ok(123));
+            });
+        }
+
+        #[test]
+        fn unreceived_message_dropped_once() {
+            let (message, drops) = DropCounter::new();
+            let (sender, receiver) = channel();
+            sender.send(message);
+            drop(receiver);
+            assert_eq!(drops.load(Relaxed), 1);
+        }
+    }
+}
@@ -0,0 +1,23 @@
+// 채널 테스트에서 메시지가 정확히 몇 번 drop 되었는지 세기 위한 타입.
+
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::Relaxed;
+
+pub struct DropCounter {
+    drops: Arc<AtomicUsize>,
+}
+
+impl DropCounter {
+    // 새 DropCounter 와, 그것이 drop 된 횟수를 읽을 수 있는 카운터를 돌려준다.
+    pub fn new() -> (DropCounter, Arc<AtomicUsize>) {
+        let drops = Arc::new(AtomicUsize::new(0));
+        (DropCounter { drops: drops.clone() }, drops)
+    }
+}
+
+impl Drop for DropCounter {
+    fn drop(&mut self) {
+        self.drops.fetch_add(1, Relaxed);
+    }
+}
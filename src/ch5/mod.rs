@@ -1 +1,3 @@
-pub mod channel;
\ No newline at end of file
+pub mod channel;
+#[cfg(test)]
+pub(crate) mod drop_counter;
\ No newline at end of file
@@ -1,13 +1,22 @@
+use std::cell::UnsafeCell;
+use std::mem::ManuallyDrop;
 use std::ops::Deref;
 use std::ptr::NonNull;
 use std::sync::atomic::{AtomicUsize, fence};
 use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
 
 struct ArcData<T> {
-    ref_count: AtomicUsize,
-    data: T,
+    // Arc 의 수
+    data_ref_count: AtomicUsize,
+    // Weak 의 수, Arc 가 하나라도 있으면 +1
+    alloc_ref_count: AtomicUsize,
+    data: UnsafeCell<ManuallyDrop<T>>,
 }
 
+// 아직 해제되지 않은 ArcData 의 수. 테스트에서 참조 카운트 실수로 새는 allocation 을 찾는 데 쓴다.
+#[cfg(test)]
+static LIVE_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
 pub struct Arc<T> {
     ptr: NonNull<ArcData<T>>,
 }
@@ -16,12 +25,23 @@ unsafe impl<T: Send + Sync> Send for Arc<T> {}
 
 unsafe impl<T: Send + Sync> Sync for Arc<T> {}
 
+pub struct Weak<T> {
+    ptr: NonNull<ArcData<T>>,
+}
+
+unsafe impl<T: Send + Sync> Send for Weak<T> {}
+
+unsafe impl<T: Send + Sync> Sync for Weak<T> {}
+
 impl<T> Arc<T> {
     pub fn new(data: T) -> Arc<T> {
+        #[cfg(test)]
+        LIVE_ALLOCATIONS.fetch_add(1, Relaxed);
         Arc {
             ptr: NonNull::from(Box::leak(Box::new(ArcData {
-                ref_count: AtomicUsize::new(1),
-                data,
+                data_ref_count: AtomicUsize::new(1),
+                alloc_ref_count: AtomicUsize::new(1),
+                data: UnsafeCell::new(ManuallyDrop::new(data)),
             })))
         }
     }
@@ -31,11 +51,35 @@ impl<T> Arc<T> {
     }
 
     pub fn get_mut(arc: &mut Self) -> Option<&mut T> {
-        if arc.data().ref_count.load(Relaxed) == 1 {
-            fence(Acquire);
-            unsafe { Some(&mut arc.ptr.as_mut().data) }
-        } else {
-            None
+        // alloc_ref_count 를 usize::MAX 로 잠가서 그 사이에 downgrade 가 일어나지 못하게 한다.
+        // Acquire 는 Weak::drop 의 Release 와 짝을 이뤄 upgrade 된 Arc 가 아래 load 에 보이게 한다.
+        if arc.data().alloc_ref_count.compare_exchange(1, usize::MAX, Acquire, Relaxed).is_err() {
+            return None;
+        }
+        let is_unique = arc.data().data_ref_count.load(Relaxed) == 1;
+        // Release 는 downgrade 의 Acquire 와 짝을 이룬다.
+        arc.data().alloc_ref_count.store(1, Release);
+        if !is_unique {
+            return None;
+        }
+        fence(Acquire);
+        unsafe { Some(&mut *arc.data().data.get()) }
+    }
+
+    pub fn downgrade(arc: &Self) -> Weak<T> {
+        let mut n = arc.data().alloc_ref_count.load(Relaxed);
+        loop {
+            if n == usize::MAX {
+                std::hint::spin_loop();
+                n = arc.data().alloc_ref_count.load(Relaxed);
+                continue;
+            }
+            assert!(n < usize::MAX - 1);
+            if let Err(e) = arc.data().alloc_ref_count.compare_exchange_weak(n, n + 1, Acquire, Relaxed) {
+                n = e;
+                continue;
+            }
+            return Weak { ptr: arc.ptr };
         }
     }
 }
@@ -44,13 +88,13 @@ impl<T> Deref for Arc<T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
-        &self.data().data
+        unsafe { &*self.data().data.get() }
     }
 }
 
 impl<T> Clone for Arc<T> {
     fn clone(&self) -> Self {
-        if self.data().ref_count.fetch_add(1, Relaxed) > usize::MAX / 2 {
+        if self.data().data_ref_count.fetch_add(1, Relaxed) > usize::MAX / 2 {
             std::process::abort();
         }
         Arc {
@@ -61,11 +105,59 @@ impl<T> Clone for Arc<T> {
 
 impl<T> Drop for Arc<T> {
     fn drop(&mut self) {
-        if self.data().ref_count.fetch_sub(1, Release) == 1 {
+        if self.data().data_ref_count.fetch_sub(1, Release) == 1 {
+            fence(Acquire);
+            unsafe {
+                ManuallyDrop::drop(&mut *self.data().data.get());
+            }
+            // 남은 Arc 가 없으므로 모든 Arc 를 대표하던 암묵적인 Weak 하나를 drop 한다.
+            drop(Weak { ptr: self.ptr });
+        }
+    }
+}
+
+impl<T> Weak<T> {
+    fn data(&self) -> &ArcData<T> {
+        unsafe { self.ptr.as_ref() }
+    }
+
+    pub fn upgrade(&self) -> Option<Arc<T>> {
+        let mut n = self.data().data_ref_count.load(Relaxed);
+        loop {
+            // 이미 0 이 되었다면 data 는 drop 되었으므로 되살리지 않는다.
+            if n == 0 {
+                return None;
+            }
+            assert!(n < usize::MAX);
+            if let Err(e) = self.data().data_ref_count.compare_exchange_weak(n, n + 1, Relaxed, Relaxed) {
+                n = e;
+                continue;
+            }
+            return Some(Arc { ptr: self.ptr });
+        }
+    }
+}
+
+impl<T> Clone for Weak<T> {
+    fn clone(&self) -> Self {
+        if self.data().alloc_ref_count.fetch_add(1, Relaxed) > usize::MAX / 2 {
+            std::process::abort();
+        }
+        Weak {
+            ptr: self.ptr
+        }
+    }
+}
+
+impl<T> Drop for Weak<T> {
+    fn drop(&mut self) {
+        if self.data().alloc_ref_count.fetch_sub(1, Release) == 1 {
             fence(Acquire);
             unsafe {
                 drop(Box::from_raw(self.ptr.as_ptr()));
             }
+            #[cfg(test)]
+            LIVE_ALLOCATIONS.fetch_sub(1, Relaxed);
         }
     }
 }
@@ -73,12 +165,29 @@ impl<T> Drop for Arc<T> {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Mutex;
     use std::sync::atomic::AtomicUsize;
-    use std::sync::atomic::Ordering::{Relaxed, Release};
-    use crate::ch6::arc::Arc;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use crate::ch5::drop_counter::DropCounter;
+    use crate::ch6::arc::{Arc, LIVE_ALLOCATIONS};
+
+    // scenario 가 만든 Arc allocation 이 끝난 뒤 모두 해제되었는지 확인한다.
+    // 카운터는 전역이므로 Arc 를 만드는 테스트는 모두 이 함수 안에서 돌려서 서로 겹치지 않게 한다.
+    pub(super) fn assert_no_leak(scenario: impl FnOnce()) {
+        static SERIAL: Mutex<()> = Mutex::new(());
+        let _serial = SERIAL.lock().unwrap_or_else(|e| e.into_inner());
+        let before = LIVE_ALLOCATIONS.load(Relaxed);
+        scenario();
+        assert_eq!(LIVE_ALLOCATIONS.load(Relaxed), before, "Arc allocation leaked");
+    }
 
     #[test]
     fn arc_test() {
+        assert_no_leak(arc_scenario);
+    }
+
+    fn arc_scenario() {
         static NUM_DROPS: AtomicUsize = AtomicUsize::new(0);
 
         struct DetectDrop;
@@ -108,4 +217,222 @@ mod tests {
 
         assert_eq!(NUM_DROPS.load(Relaxed), 1);
     }
+
+    #[test]
+    fn weak_test() {
+        assert_no_leak(weak_scenario);
+    }
+
+    fn weak_scenario() {
+        static NUM_DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        struct DetectDrop;
+
+        impl Drop for DetectDrop {
+            fn drop(&mut self) {
+                NUM_DROPS.fetch_add(1, Relaxed);
+            }
+        }
+
+        let x = Arc::new(("hello", DetectDrop));
+        let y = Arc::downgrade(&x);
+        let z = Arc::downgrade(&x);
+
+        let t = std::thread::spawn(move || {
+            let y = y.upgrade().unwrap();
+            assert_eq!(y.0, "hello");
+        });
+        assert_eq!(x.0, "hello");
+        t.join().unwrap();
+
+        assert_eq!(NUM_DROPS.load(Relaxed), 0);
+        assert!(z.upgrade().is_some());
+
+        drop(x);
+
+        assert_eq!(NUM_DROPS.load(Relaxed), 1);
+        assert!(z.upgrade().is_none());
+
+        // 남은 Weak 하나(z)가 allocation 의 마지막 참조다.
+        let sentinel = z.clone();
+        assert_eq!(z.data().alloc_ref_count.load(Relaxed), 2);
+        drop(z);
+        assert_eq!(sentinel.data().alloc_ref_count.load(Relaxed), 1);
+        drop(sentinel);
+
+        assert_eq!(NUM_DROPS.load(Relaxed), 1);
+    }
+
+    // 값은 마지막 Arc 와 함께 정확히 한 번 drop 되고, allocation 은 마지막 Weak 와 함께 해제된다.
+    #[test]
+    fn weak_outlives_strong() {
+        assert_no_leak(|| {
+            let (payload, drops) = DropCounter::new();
+            let before = LIVE_ALLOCATIONS.load(Relaxed);
+            let x = Arc::new(payload);
+            let w = Arc::downgrade(&x);
+            assert_eq!(LIVE_ALLOCATIONS.load(Relaxed), before + 1);
+
+            drop(x);
+            assert_eq!(drops.load(Relaxed), 1);
+            assert!(w.upgrade().is_none());
+            // Weak 가 남아 있으므로 allocation 은 아직 살아 있다.
+            assert_eq!(LIVE_ALLOCATIONS.load(Relaxed), before + 1);
+
+            drop(w);
+            assert_eq!(LIVE_ALLOCATIONS.load(Relaxed), before);
+            assert_eq!(drops.load(Relaxed), 1);
+        });
+    }
+
+    #[test]
+    fn get_mut_test() {
+        assert_no_leak(get_mut_scenario);
+    }
+
+    fn get_mut_scenario() {
+        let mut x = Arc::new(1);
+        let y = x.clone();
+
+        assert!(Arc::get_mut(&mut x).is_none());
+
+        drop(y);
+
+        let w = Arc::downgrade(&x);
+        assert!(Arc::get_mut(&mut x).is_none());
+        drop(w);
+
+        *Arc::get_mut(&mut x).unwrap() += 1;
+        assert_eq!(*x, 2);
+    }
+
+    #[test]
+    fn clone_heavy_no_leak() {
+        assert_no_leak(|| {
+            let x = Arc::new(String::from("hello"));
+            thread::scope(|s| {
+                for _ in 0..4 {
+                    s.spawn(|| {
+                        let mut clones = Vec::new();
+                        for _ in 0..1000 {
+                            clones.push(x.clone());
+                        }
+                        assert!(clones.iter().all(|c| **c == "hello"));
+                    });
+                }
+            });
+            drop(x);
+        });
+    }
+
+    #[test]
+    fn weak_heavy_no_leak() {
+        assert_no_leak(|| {
+            let x = Arc::new(vec![1, 2, 3]);
+            let weaks: Vec<_> = (0..100).map(|_| Arc::downgrade(&x)).collect();
+            thread::scope(|s| {
+                for w in &weaks {
+                    s.spawn(move || {
+                        let upgraded = w.upgrade().unwrap();
+                        drop(w.clone());
+                        assert_eq!(upgraded.len(), 3);
+                    });
+                }
+            });
+            // Arc 를 먼저 없애고 Weak 가 마지막으로 allocation 을 해제한다.
+            drop(x);
+            assert!(weaks.iter().all(|w| w.upgrade().is_none()));
+            drop(weaks);
+        });
+    }
+}
+
+// `cargo test --release bench -- --ignored --nocapture` 로 실행한다.
+// 마지막 drop 에서만 Acquire fence 를 쓰는 Arc 와, 매 drop 마다 AcqRel 로 감소시키는 Arc 를 비교한다.
+// x86 에서는 두 경우 모두 같은 lock 명령이 되어 차이가 거의 없고, ARM 같은 곳에서 차이가 드러난다.
+#[cfg(test)]
+mod bench {
+    use std::ptr::NonNull;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::{AcqRel, Relaxed};
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use crate::ch6::arc::Arc;
+
+    const THREADS: usize = 4;
+    const ITERATIONS: usize = 1_000_000;
+
+    struct NaiveArcData<T> {
+        ref_count: AtomicUsize,
+        // 비교를 위해 Arc 와 같은 크기로 할당할 뿐 읽지는 않는다.
+        #[allow(dead_code)]
+        data: T,
+    }
+
+    struct NaiveArc<T> {
+        ptr: NonNull<NaiveArcData<T>>,
+    }
+
+    unsafe impl<T: Send + Sync> Send for NaiveArc<T> {}
+
+    unsafe impl<T: Send + Sync> Sync for NaiveArc<T> {}
+
+    impl<T> NaiveArc<T> {
+        fn new(data: T) -> NaiveArc<T> {
+            NaiveArc {
+                ptr: NonNull::from(Box::leak(Box::new(NaiveArcData {
+                    ref_count: AtomicUsize::new(1),
+                    data,
+                })))
+            }
+        }
+
+        fn data(&self) -> &NaiveArcData<T> {
+            unsafe { self.ptr.as_ref() }
+        }
+    }
+
+    impl<T> Clone for NaiveArc<T> {
+        fn clone(&self) -> Self {
+            self.data().ref_count.fetch_add(1, Relaxed);
+            NaiveArc {
+                ptr: self.ptr
+            }
+        }
+    }
+
+    impl<T> Drop for NaiveArc<T> {
+        fn drop(&mut self) {
+            if self.data().ref_count.fetch_sub(1, AcqRel) == 1 {
+                unsafe {
+                    drop(Box::from_raw(self.ptr.as_ptr()));
+                }
+            }
+        }
+    }
+
+    fn clone_and_drop<A: Clone + Sync>(arc: &A) -> Duration {
+        let start = Instant::now();
+        thread::scope(|s| {
+            for _ in 0..THREADS {
+                s.spawn(|| {
+                    for _ in 0..ITERATIONS {
+                        drop(std::hint::black_box(arc.clone()));
+                    }
+                });
+            }
+        });
+        start.elapsed()
+    }
+
+    #[test]
+    #[ignore]
+    fn fence_vs_acq_rel() {
+        let fence = clone_and_drop(&Arc::new(0u64));
+        let naive = clone_and_drop(&NaiveArc::new(0u64));
+
+        let ops = (THREADS * ITERATIONS) as f64;
+        println!("fence:   {:.2} ns/op", fence.as_nanos() as f64 / ops);
+        println!("acq_rel: {:.2} ns/op", naive.as_nanos() as f64 / ops);
+    }
 }
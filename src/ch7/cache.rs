@@ -0,0 +1,50 @@
+// 7장: 캐시와 false sharing
+// 서로 다른 스레드가 쓰는 값들이 같은 64 바이트 캐시 라인에 있으면, 값이 달라도 캐시 라인이 코어 사이를 오간다.
+// 값 하나가 캐시 라인 하나를 혼자 쓰도록 정렬하면 이를 피할 수 있다.
+
+use std::ops::{Deref, DerefMut};
+
+#[repr(align(64))]
+pub struct CachePadded<T> {
+    value: T,
+}
+
+impl<T> CachePadded<T> {
+    pub const fn new(value: T) -> Self {
+        Self { value }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T> Deref for CachePadded<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+impl<T> DerefMut for CachePadded<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::mem::{align_of, size_of};
+    use std::sync::atomic::AtomicBool;
+    use crate::ch7::cache::CachePadded;
+
+    #[test]
+    fn one_value_per_cache_line() {
+        assert_eq!(align_of::<CachePadded<AtomicBool>>(), 64);
+        assert_eq!(size_of::<[CachePadded<AtomicBool>; 2]>(), 128);
+
+        let mut padded = CachePadded::new(1);
+        *padded += 1;
+        assert_eq!(padded.into_inner(), 2);
+    }
+}
@@ -0,0 +1,103 @@
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed, Release};
+use crate::ch9::futex::{wait, wake_all};
+
+// n 개의 스레드가 모두 wait 를 호출할 때까지 막았다가 한꺼번에 풀어 주는 배리어.
+// generation 이 바뀌는 것으로 한 번의 배리어가 끝났음을 알리므로 여러 번 다시 쓸 수 있다.
+pub struct Barrier {
+    n: u32,
+    count: AtomicU32,
+    generation: AtomicU32,
+}
+
+impl Barrier {
+    pub const fn new(n: u32) -> Self {
+        assert!(n > 0, "barrier needs at least one thread");
+        Self {
+            n,
+            count: AtomicU32::new(0),
+            generation: AtomicU32::new(0),
+        }
+    }
+
+    // 마지막으로 도착한 스레드(leader)만 true 를 돌려받는다.
+    pub fn wait(&self) -> bool {
+        // count 를 올리기 전에 세대를 읽어 두어야 마지막 스레드의 wake 를 놓치지 않는다.
+        let generation = self.generation.load(Acquire);
+
+        if self.count.fetch_add(1, AcqRel) + 1 == self.n {
+            // 다음 세대를 위해 count 를 먼저 0 으로 되돌린 뒤에 세대를 바꾼다.
+            // 그래서 깨어나자마자 다시 wait 를 호출한 스레드도 0 부터 센다.
+            self.count.store(0, Relaxed);
+            self.generation.fetch_add(1, Release);
+            wake_all(&self.generation);
+            return true;
+        }
+
+        // 깨어난 것이 spurious wakeup 일 수 있으므로 세대가 바뀔 때까지 기다린다.
+        while self.generation.load(Acquire) == generation {
+            wait(&self.generation, generation);
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU32;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::sync::Mutex;
+    use std::thread;
+    use std::time::{Duration, Instant};
+    use crate::ch9::barrier::Barrier;
+
+    #[test]
+    fn all_threads_pass_together() {
+        let barrier = Barrier::new(4);
+        let arrived = Mutex::new(Vec::new());
+        let passed = Mutex::new(Vec::new());
+
+        thread::scope(|s| {
+            for i in 0..4 {
+                let (barrier, arrived, passed) = (&barrier, &arrived, &passed);
+                s.spawn(move || {
+                    thread::sleep(Duration::from_millis(10 * i));
+                    arrived.lock().unwrap().push(Instant::now());
+                    barrier.wait();
+                    passed.lock().unwrap().push(Instant::now());
+                });
+            }
+        });
+
+        let last_arrived = *arrived.lock().unwrap().iter().max().unwrap();
+        let passed = passed.into_inner().unwrap();
+        assert_eq!(passed.len(), 4);
+        assert!(passed.iter().all(|&t| t >= last_arrived));
+    }
+
+    #[test]
+    fn reusable_across_generations() {
+        const ROUNDS: u32 = 100;
+        let barrier = Barrier::new(4);
+        let arrived = AtomicU32::new(0);
+        let leaders = AtomicU32::new(0);
+
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for round in 0..ROUNDS {
+                        arrived.fetch_add(1, Relaxed);
+                        if barrier.wait() {
+                            leaders.fetch_add(1, Relaxed);
+                        }
+                        // 이번 세대의 네 스레드가 모두 도착한 뒤에만 여기에 온다.
+                        assert!(arrived.load(Relaxed) >= 4 * (round + 1));
+                    }
+                });
+            }
+        });
+
+        assert_eq!(arrived.load(Relaxed), 4 * ROUNDS);
+        assert_eq!(leaders.load(Relaxed), ROUNDS);
+    }
+}
@@ -0,0 +1,83 @@
+use std::sync::atomic::{AtomicU32, AtomicUsize};
+use std::sync::atomic::Ordering::Relaxed;
+use crate::ch9::futex::{wait, wake_all, wake_one};
+use crate::ch9::mutex::{LockResult, MutexGuard};
+
+pub struct Condvar {
+    counter: AtomicU32,
+    num_waiters: AtomicUsize,
+}
+
+impl Condvar {
+    pub const fn new() -> Self {
+        Self {
+            counter: AtomicU32::new(0),
+            num_waiters: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn notify_one(&self) {
+        if self.num_waiters.load(Relaxed) > 0 {
+            self.counter.fetch_add(1, Relaxed);
+            wake_one(&self.counter);
+        }
+    }
+
+    pub fn notify_all(&self) {
+        if self.num_waiters.load(Relaxed) > 0 {
+            self.counter.fetch_add(1, Relaxed);
+            wake_all(&self.counter);
+        }
+    }
+
+    // 다시 잠글 때 mutex 가 poison 되어 있으면 Mutex::lock 처럼 PoisonError 를 돌려준다.
+    pub fn wait<'a, T>(&self, guard: MutexGuard<'a, T>) -> LockResult<MutexGuard<'a, T>> {
+        self.num_waiters.fetch_add(1, Relaxed);
+
+        // unlock 하기 전에 counter 를 읽어 두어야 그 사이의 notify 를 놓치지 않는다.
+        let counter_value = self.counter.load(Relaxed);
+
+        let mutex = guard.mutex;
+        drop(guard);
+
+        wait(&self.counter, counter_value);
+
+        self.num_waiters.fetch_sub(1, Relaxed);
+
+        mutex.lock()
+    }
+}
+
+impl Default for Condvar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+    use crate::ch9::condvar::Condvar;
+    use crate::ch9::mutex::Mutex;
+
+    #[test]
+    fn condvar_test() {
+        let queue = Mutex::new(Vec::new());
+        let not_empty = Condvar::new();
+
+        thread::scope(|s| {
+            s.spawn(|| {
+                thread::sleep(Duration::from_millis(100));
+                queue.lock().unwrap().push(123);
+                not_empty.notify_one();
+            });
+
+            let mut q = queue.lock().unwrap();
+            while q.is_empty() {
+                q = not_empty.wait(q).unwrap();
+            }
+            assert_eq!(q.pop(), Some(123));
+        });
+    }
+}
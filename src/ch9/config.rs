@@ -0,0 +1,72 @@
+use crate::ch9::seqlock::SeqLock;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Config {
+    pub timeout_ms: u64,
+    pub retries: u32,
+}
+
+impl Config {
+    // SeqLock 은 word 만 담으므로 필드마다 word 하나씩 쓴다. retries 뒤의 padding 은 옮기지 않는다.
+    const fn to_words(self) -> [u64; 2] {
+        [self.timeout_ms, self.retries as u64]
+    }
+
+    const fn from_words(words: [u64; 2]) -> Self {
+        Self { timeout_ms: words[0], retries: words[1] as u32 }
+    }
+}
+
+// 가끔 바뀌고 자주 읽히는 설정. reader 는 잠금 없이 SeqLock 의 재시도 루프로 읽으므로
+// writer 가 바꾸는 중이어도 두 설정이 섞인 값을 보지 않는다.
+pub struct ConfigStore {
+    config: SeqLock<2>,
+}
+
+impl ConfigStore {
+    pub const fn new(config: Config) -> Self {
+        Self { config: SeqLock::new(config.to_words()) }
+    }
+
+    pub fn read(&self) -> Config {
+        Config::from_words(self.config.read())
+    }
+
+    pub fn update(&self, config: Config) {
+        self.config.write(config.to_words());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use crate::ch9::config::{Config, ConfigStore};
+
+    #[test]
+    fn readers_never_see_a_mix() {
+        const A: Config = Config { timeout_ms: 100, retries: 3 };
+        const B: Config = Config { timeout_ms: u64::MAX, retries: u32::MAX };
+        let updates = if cfg!(miri) { 200 } else { 50_000 };
+        let store = ConfigStore::new(A);
+        let done = AtomicBool::new(false);
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    while !done.load(Relaxed) {
+                        let config = store.read();
+                        assert!(config == A || config == B, "mixed config: {config:?}");
+                    }
+                });
+            }
+            s.spawn(|| {
+                for i in 0..updates {
+                    store.update(if i % 2 == 0 { B } else { A });
+                }
+                done.store(true, Relaxed);
+            });
+        });
+        assert_eq!(store.read(), A);
+    }
+}
@@ -0,0 +1,132 @@
+use std::cell::UnsafeCell;
+use std::ops::Deref;
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release, SeqCst};
+use std::thread;
+
+// 두 개의 버퍼를 번갈아 쓴다. reader 는 front 를 읽고, producer 는 back 을 채운 뒤 publish 로 둘을 바꾼다.
+// 바꾼 직후에도 예전 front 를 읽던 reader 가 남아 있을 수 있으므로, producer 는 back 의 reader 수가
+// 0 이 될 때까지 기다린 뒤에 쓴다. producer 는 하나만 있어야 하고, 동시에 쓰려고 하면 panic 한다.
+pub struct DoubleBuffer<T> {
+    buffers: [UnsafeCell<T>; 2],
+    front: AtomicUsize,
+    // 버퍼마다 지금 읽고 있는 reader 의 수
+    readers: [AtomicUsize; 2],
+    writing: AtomicBool,
+}
+
+unsafe impl<T> Sync for DoubleBuffer<T> where T: Send + Sync {}
+
+pub struct ReadGuard<'a, T> {
+    buffer: &'a DoubleBuffer<T>,
+    index: usize,
+}
+
+impl<T: Clone> DoubleBuffer<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            buffers: [UnsafeCell::new(value.clone()), UnsafeCell::new(value)],
+            front: AtomicUsize::new(0),
+            readers: [AtomicUsize::new(0), AtomicUsize::new(0)],
+            writing: AtomicBool::new(false),
+        }
+    }
+}
+
+impl<T> DoubleBuffer<T> {
+    pub fn read(&self) -> ReadGuard<'_, T> {
+        loop {
+            let index = self.front.load(Acquire);
+            self.readers[index].fetch_add(1, SeqCst);
+            // 세기 전에 publish 가 있었다면 producer 가 이 reader 를 못 보고 쓰기 시작했을 수 있으므로 다시 한다.
+            // 세고 나서 여전히 front 라면, producer 는 이 버퍼에 쓰기 전에 반드시 이 reader 를 본다.
+            if self.front.load(SeqCst) == index {
+                return ReadGuard { buffer: self, index };
+            }
+            self.readers[index].fetch_sub(1, Release);
+        }
+    }
+
+    // back 버퍼를 f 로 채운다. publish 하기 전까지 reader 에게는 보이지 않는다.
+    pub fn write_back(&self, f: impl FnOnce(&mut T)) {
+        assert!(!self.writing.swap(true, Acquire), "DoubleBuffer has a single producer");
+        let back = 1 - self.front.load(Relaxed);
+        // reader 의 Release 감소와 짝을 이뤄, 마지막 reader 가 다 읽은 뒤에 쓴다.
+        // SeqCst 는 read 의 fetch_add 와 다시 확인하는 load 와 짝을 이룬다.
+        while self.readers[back].load(SeqCst) != 0 {
+            thread::yield_now();
+        }
+        f(unsafe { &mut *self.buffers[back].get() });
+        self.writing.store(false, Release);
+    }
+
+    // back 과 front 를 바꾼다. 이후의 read 는 방금 채운 버퍼를 본다.
+    pub fn publish(&self) {
+        assert!(!self.writing.swap(true, Acquire), "DoubleBuffer has a single producer");
+        let back = 1 - self.front.load(Relaxed);
+        // write_back 에서 쓴 것이 새 front 를 읽는 reader 에게 보이게 한다(SeqCst 는 Release 를 포함한다).
+        self.front.store(back, SeqCst);
+        self.writing.store(false, Release);
+    }
+}
+
+impl<T> Deref for ReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.buffer.buffers[self.index].get() }
+    }
+}
+
+impl<T> Drop for ReadGuard<'_, T> {
+    fn drop(&mut self) {
+        self.buffer.readers[self.index].fetch_sub(1, Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use crate::ch9::doublebuffer::DoubleBuffer;
+
+    #[test]
+    fn readers_see_whole_buffers() {
+        let buffer = DoubleBuffer::new([0u64; 16]);
+        let done = AtomicBool::new(false);
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    let mut last = 0;
+                    while !done.load(Relaxed) {
+                        let frame = buffer.read();
+                        assert!(frame.iter().all(|&v| v == frame[0]), "torn frame: {:?}", *frame);
+                        // producer 가 하나이므로 front 는 뒤로 가지 않는다.
+                        assert!(frame[0] >= last);
+                        last = frame[0];
+                        drop(frame);
+                        thread::yield_now();
+                    }
+                });
+            }
+            s.spawn(|| {
+                for i in 1..=2_000 {
+                    buffer.write_back(|frame| frame.fill(i));
+                    buffer.publish();
+                }
+                done.store(true, Relaxed);
+            });
+        });
+        assert_eq!(*buffer.read(), [2_000; 16]);
+    }
+
+    #[test]
+    fn write_back_is_hidden_until_publish() {
+        let buffer = DoubleBuffer::new(0);
+        buffer.write_back(|v| *v = 1);
+        assert_eq!(*buffer.read(), 0);
+        buffer.publish();
+        assert_eq!(*buffer.read(), 1);
+    }
+}
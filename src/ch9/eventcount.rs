@@ -0,0 +1,145 @@
+use std::sync::Mutex;
+use std::sync::atomic::{fence, AtomicU64, AtomicUsize};
+use std::sync::atomic::Ordering::SeqCst;
+use std::thread;
+use std::thread::Thread;
+
+// Dmitry Vyukov 의 event count.
+// 잠금 없이 조건을 확인하는 자료구조(lock-free queue 등)에서, 조건 확인과 잠들기 사이의 notify 를 놓치지 않게 한다.
+//
+// waiter:   let key = ec.prepare_wait();
+//           if 조건 { ec.cancel(key) } else { ec.wait(key) }
+// notifier: 조건을 바꾼 뒤 ec.notify_one() / ec.notify_all()
+//
+// prepare_wait 이후에 일어난 notify 는 epoch 를 바꾸므로, wait 는 바로 돌아온다.
+pub struct EventCount {
+    epoch: AtomicU64,
+    num_waiters: AtomicUsize,
+    waiting: Mutex<Vec<Thread>>,
+}
+
+// prepare_wait 가 돌려주는, 기다리기 시작한 시점의 epoch.
+#[must_use]
+pub struct Key(u64);
+
+impl EventCount {
+    pub const fn new() -> Self {
+        Self {
+            epoch: AtomicU64::new(0),
+            num_waiters: AtomicUsize::new(0),
+            waiting: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn prepare_wait(&self) -> Key {
+        self.waiting.lock().unwrap().push(thread::current());
+        self.num_waiters.fetch_add(1, SeqCst);
+        let key = Key(self.epoch.load(SeqCst));
+        // 이후에 호출하는 쪽이 조건을 읽는 것이 num_waiters 를 올린 것보다 먼저 일어나지 않게 한다.
+        // notify 쪽의 fence 와 짝을 이루어, 둘 중 적어도 하나는 상대의 변경을 본다.
+        fence(SeqCst);
+        key
+    }
+
+    pub fn cancel(&self, _key: Key) {
+        self.unregister();
+    }
+
+    // 돌아온 뒤에도 조건이 만족되었다는 보장은 없으므로, 호출한 쪽이 다시 확인해야 한다.
+    pub fn wait(&self, key: Key) {
+        while self.epoch.load(SeqCst) == key.0 {
+            thread::park();
+        }
+        self.unregister();
+    }
+
+    pub fn notify_one(&self) {
+        if self.bump() {
+            if let Some(t) = self.waiting.lock().unwrap().pop() {
+                t.unpark();
+            }
+        }
+    }
+
+    pub fn notify_all(&self) {
+        if self.bump() {
+            for t in self.waiting.lock().unwrap().drain(..) {
+                t.unpark();
+            }
+        }
+    }
+
+    // 기다리는 스레드가 없으면 epoch 도 바꾸지 않고 잠금도 잡지 않는다.
+    fn bump(&self) -> bool {
+        fence(SeqCst);
+        if self.num_waiters.load(SeqCst) == 0 {
+            return false;
+        }
+        self.epoch.fetch_add(1, SeqCst);
+        true
+    }
+
+    fn unregister(&self) {
+        let id = thread::current().id();
+        self.waiting.lock().unwrap().retain(|t| t.id() != id);
+        self.num_waiters.fetch_sub(1, SeqCst);
+    }
+}
+
+impl Default for EventCount {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering::{Acquire, Release};
+    use std::thread;
+    use std::time::Duration;
+    use crate::ch9::eventcount::EventCount;
+
+    #[test]
+    fn waiter_wakes_after_notify() {
+        let ec = EventCount::new();
+        let flag = AtomicBool::new(false);
+
+        thread::scope(|s| {
+            let waiter = s.spawn(|| {
+                let mut rounds = 0;
+                loop {
+                    if flag.load(Acquire) {
+                        break;
+                    }
+                    let key = ec.prepare_wait();
+                    if flag.load(Acquire) {
+                        ec.cancel(key);
+                        break;
+                    }
+                    ec.wait(key);
+                    rounds += 1;
+                }
+                rounds
+            });
+
+            thread::sleep(Duration::from_millis(20));
+            flag.store(true, Release);
+            ec.notify_all();
+
+            // spin 하지 않고 notify 한 번에 깨어났는지 확인한다.
+            assert!(waiter.join().unwrap() <= 1);
+        });
+    }
+
+    #[test]
+    fn notify_without_waiters() {
+        let ec = EventCount::new();
+        ec.notify_one();
+        ec.notify_all();
+        let key = ec.prepare_wait();
+        ec.notify_one();
+        // prepare_wait 이후의 notify 를 보았으므로 바로 돌아온다.
+        ec.wait(key);
+    }
+}
@@ -0,0 +1,45 @@
+// atomic-wait 크레이트와 같은 wait/wake 연산.
+// Linux 에서는 futex 시스템 콜을 직접 사용하고, 다른 플랫폼에서는 spin 으로 대신한다.
+
+use std::sync::atomic::AtomicU32;
+
+#[cfg(target_os = "linux")]
+pub fn wait(a: &AtomicU32, expected: u32) {
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            a as *const AtomicU32,
+            libc::FUTEX_WAIT | libc::FUTEX_PRIVATE_FLAG,
+            expected,
+            std::ptr::null::<libc::timespec>(),
+        );
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn wake_one(a: &AtomicU32) {
+    unsafe {
+        libc::syscall(libc::SYS_futex, a as *const AtomicU32, libc::FUTEX_WAKE | libc::FUTEX_PRIVATE_FLAG, 1);
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub fn wake_all(a: &AtomicU32) {
+    unsafe {
+        libc::syscall(libc::SYS_futex, a as *const AtomicU32, libc::FUTEX_WAKE | libc::FUTEX_PRIVATE_FLAG, i32::MAX);
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn wait(a: &AtomicU32, expected: u32) {
+    use std::sync::atomic::Ordering::Relaxed;
+    while a.load(Relaxed) == expected {
+        std::thread::yield_now();
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn wake_one(_a: &AtomicU32) {}
+
+#[cfg(not(target_os = "linux"))]
+pub fn wake_all(_a: &AtomicU32) {}
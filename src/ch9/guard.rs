@@ -0,0 +1,66 @@
+// 스코프를 벗어날 때(panic 으로 unwind 될 때 포함) 클로저를 실행하는 guard.
+// 임계 구역 안에서 panic 이 나더라도 atomic 상태를 되돌려야 할 때 쓴다.
+pub struct ScopeGuard<F: FnMut()> {
+    // cancel 하면 None. 클로저가 잡은 값은 그때 drop 된다.
+    f: Option<F>,
+}
+
+pub fn defer<F: FnMut()>(f: F) -> ScopeGuard<F> {
+    ScopeGuard { f: Some(f) }
+}
+
+impl<F: FnMut()> ScopeGuard<F> {
+    // 정상적으로 끝났을 때처럼, 클로저를 실행하지 않고 guard 를 없앤다.
+    pub fn cancel(mut self) {
+        self.f = None;
+    }
+}
+
+impl<F: FnMut()> Drop for ScopeGuard<F> {
+    fn drop(&mut self) {
+        if let Some(f) = &mut self.f {
+            f();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+    use std::sync::atomic::Ordering::Relaxed;
+    use crate::ch5::drop_counter::DropCounter;
+    use crate::ch9::guard::defer;
+
+    #[test]
+    fn runs_on_unwind() {
+        let ran = Cell::new(false);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            let _guard = defer(|| ran.set(true));
+            panic!("critical section failed");
+        }));
+        assert!(result.is_err());
+        assert!(ran.get());
+    }
+
+    #[test]
+    fn cancel_skips_closure() {
+        let ran = Cell::new(false);
+        defer(|| ran.set(true)).cancel();
+        assert!(!ran.get());
+        drop(defer(|| ran.set(true)));
+        assert!(ran.get());
+    }
+
+    // cancel 해도 클로저가 잡은 값은 새지 않고 drop 된다.
+    #[test]
+    fn cancel_drops_captures() {
+        let (captured, drops) = DropCounter::new();
+        let guard = defer(move || {
+            let _keep = &captured;
+            panic!("must not run");
+        });
+        guard.cancel();
+        assert_eq!(drops.load(Relaxed), 1);
+    }
+}
@@ -0,0 +1,115 @@
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::Relaxed;
+use std::time::{Duration, Instant};
+use crate::ch9::mutex::{LockResult, Mutex, MutexGuard, PoisonError};
+
+// 잠금을 쥐고 있던 시간을 모은다. 오래 잡히는 잠금을 찾는 데 쓴다.
+// 각 값은 따로 갱신되는 통계이므로 Relaxed 로 충분하다.
+pub struct HoldTimer {
+    count: AtomicU64,
+    total_nanos: AtomicU64,
+    max_nanos: AtomicU64,
+}
+
+// MutexGuard 와 같지만, drop 될 때 lock 부터 지금까지의 시간을 HoldTimer 에 기록한다.
+pub struct TimedGuard<'a, T> {
+    guard: MutexGuard<'a, T>,
+    timer: &'a HoldTimer,
+    start: Instant,
+}
+
+impl HoldTimer {
+    pub const fn new() -> Self {
+        Self {
+            count: AtomicU64::new(0),
+            total_nanos: AtomicU64::new(0),
+            max_nanos: AtomicU64::new(0),
+        }
+    }
+
+    pub fn lock<'a, T>(&'a self, mutex: &'a Mutex<T>) -> LockResult<TimedGuard<'a, T>> {
+        match mutex.lock() {
+            Ok(guard) => Ok(self.attach(guard)),
+            Err(e) => Err(PoisonError::new(self.attach(e.into_inner()))),
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Relaxed)
+    }
+
+    pub fn total(&self) -> Duration {
+        Duration::from_nanos(self.total_nanos.load(Relaxed))
+    }
+
+    pub fn max(&self) -> Duration {
+        Duration::from_nanos(self.max_nanos.load(Relaxed))
+    }
+
+    fn attach<'a, T>(&'a self, guard: MutexGuard<'a, T>) -> TimedGuard<'a, T> {
+        TimedGuard { guard, timer: self, start: Instant::now() }
+    }
+
+    fn record(&self, held: Duration) {
+        let nanos = held.as_nanos().try_into().unwrap_or(u64::MAX);
+        self.count.fetch_add(1, Relaxed);
+        self.total_nanos.fetch_add(nanos, Relaxed);
+        self.max_nanos.fetch_max(nanos, Relaxed);
+    }
+}
+
+impl Default for HoldTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Deref for TimedGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> DerefMut for TimedGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.guard
+    }
+}
+
+impl<T> Drop for TimedGuard<'_, T> {
+    // guard 필드는 이 함수가 끝난 뒤에 drop 되므로, 기록하는 동안에도 잠금은 아직 쥐고 있다.
+    fn drop(&mut self) {
+        self.timer.record(self.start.elapsed());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+    use crate::ch9::instrument::HoldTimer;
+    use crate::ch9::mutex::Mutex;
+
+    #[test]
+    fn records_hold_time() {
+        let mutex = Mutex::new(0);
+        let timer = HoldTimer::new();
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| *timer.lock(&mutex).unwrap() += 1);
+            }
+        });
+        {
+            let mut guard = timer.lock(&mutex).unwrap();
+            thread::sleep(Duration::from_millis(20));
+            *guard += 1;
+        }
+        assert_eq!(*mutex.lock().unwrap(), 5);
+        assert_eq!(timer.count(), 5);
+        assert!(timer.max() >= Duration::from_millis(20));
+        assert!(timer.total() >= timer.max());
+    }
+}
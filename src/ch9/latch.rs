@@ -0,0 +1,82 @@
+use std::sync::Mutex;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::{Acquire, Release};
+use std::thread;
+use std::thread::Thread;
+
+// 정해진 횟수만큼 count_down 되면 열리는 한 번짜리 latch.
+// Barrier 와 달리 count_down 하는 스레드와 wait 하는 스레드가 서로 다르고, 다시 닫히지 않는다.
+pub struct CountDownLatch {
+    count: AtomicUsize,
+    waiting: Mutex<Vec<Thread>>,
+}
+
+impl CountDownLatch {
+    pub const fn new(count: usize) -> Self {
+        Self {
+            count: AtomicUsize::new(count),
+            waiting: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count.load(Acquire)
+    }
+
+    pub fn count_down(&self) {
+        let previous = self
+            .count
+            .fetch_update(Release, Acquire, |count| count.checked_sub(1))
+            .expect("count_down called more times than the latch count");
+        if previous == 1 {
+            for t in self.waiting.lock().unwrap().drain(..) {
+                t.unpark();
+            }
+        }
+    }
+
+    pub fn wait(&self) {
+        if self.count() == 0 {
+            return;
+        }
+        // count 를 다시 확인하기 전에 먼저 등록해야 마지막 count_down 의 unpark 를 놓치지 않는다.
+        self.waiting.lock().unwrap().push(thread::current());
+        while self.count() != 0 {
+            thread::park();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use std::time::Duration;
+    use crate::ch9::latch::CountDownLatch;
+
+    #[test]
+    fn coordinator_waits_for_workers() {
+        let latch = CountDownLatch::new(3);
+        let done = AtomicUsize::new(0);
+
+        thread::scope(|s| {
+            for i in 1..=3 {
+                let (latch, done) = (&latch, &done);
+                s.spawn(move || {
+                    thread::sleep(Duration::from_millis(10 * i));
+                    done.fetch_add(1, Relaxed);
+                    latch.count_down();
+                });
+            }
+
+            latch.wait();
+            // count_down 의 Release 와 wait 의 Acquire 덕분에 세 스레드의 작업이 모두 보인다.
+            assert_eq!(done.load(Relaxed), 3);
+            assert_eq!(latch.count(), 0);
+        });
+
+        // 이미 열린 latch 는 바로 통과한다.
+        latch.wait();
+    }
+}
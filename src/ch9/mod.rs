@@ -0,0 +1,14 @@
+pub mod barrier;
+pub mod condvar;
+pub mod config;
+pub mod doublebuffer;
+pub mod eventcount;
+pub mod futex;
+pub mod guard;
+pub mod instrument;
+pub mod latch;
+pub mod mutex;
+pub mod rwlock;
+pub mod semaphore;
+pub mod seqlock;
+pub mod waitgroup;
\ No newline at end of file
@@ -0,0 +1,264 @@
+use std::cell::UnsafeCell;
+use std::error::Error;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+#[cfg(feature = "contention-stats")]
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::{AtomicBool, AtomicU32};
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+use std::thread;
+use crate::ch9::futex::{wait, wake_one};
+
+pub struct Mutex<T> {
+    // 0: 잠금 해제
+    // 1: 잠김, 대기하는 스레드 없음
+    // 2: 잠김, 대기하는 스레드 있음
+    state: AtomicU32,
+    // 잠금을 쥔 스레드가 panic 했으면 true. 값이 중간 상태로 남아 있을 수 있다는 뜻이다.
+    poisoned: AtomicBool,
+    // try_lock_counted 가 잠금을 얻지 못한 횟수
+    #[cfg(feature = "contention-stats")]
+    contended: AtomicU64,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T> Sync for Mutex<T> where T: Send {}
+
+pub struct MutexGuard<'a, T> {
+    pub(crate) mutex: &'a Mutex<T>,
+    // 이미 panic 하는 중에 잠근 guard 는 drop 될 때 poison 하지 않는다.
+    panicking: bool,
+}
+
+// std::sync::PoisonError 처럼, poison 되었더라도 guard 를 꺼내 쓸 수 있게 한다.
+pub struct PoisonError<T> {
+    guard: T,
+}
+
+pub type LockResult<T> = Result<T, PoisonError<T>>;
+
+impl<T> PoisonError<T> {
+    pub fn new(guard: T) -> Self {
+        Self { guard }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.guard
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.guard
+    }
+}
+
+impl<T> fmt::Debug for PoisonError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PoisonError").finish_non_exhaustive()
+    }
+}
+
+impl<T> fmt::Display for PoisonError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "poisoned lock: another thread panicked while holding it")
+    }
+}
+
+impl<T> Error for PoisonError<T> {}
+
+unsafe impl<T> Sync for MutexGuard<'_, T> where T: Sync {}
+
+impl<T> Mutex<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            state: AtomicU32::new(0),
+            poisoned: AtomicBool::new(false),
+            #[cfg(feature = "contention-stats")]
+            contended: AtomicU64::new(0),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
+        if self.state.compare_exchange(0, 1, Acquire, Relaxed).is_err() {
+            lock_contended(&self.state);
+        }
+        self.guard()
+    }
+
+    pub fn try_lock(&self) -> Option<LockResult<MutexGuard<'_, T>>> {
+        self.state
+            .compare_exchange(0, 1, Acquire, Relaxed)
+            .ok()
+            .map(|_| self.guard())
+    }
+
+    // try_lock 과 같지만, `--features contention-stats` 로 빌드하면 실패할 때마다 횟수를 센다.
+    // 기능을 끄면 세지 않으므로 try_lock 과 비용이 같다.
+    pub fn try_lock_counted(&self) -> Option<LockResult<MutexGuard<'_, T>>> {
+        let guard = self.try_lock();
+        #[cfg(feature = "contention-stats")]
+        if guard.is_none() {
+            // 통계일 뿐 다른 메모리를 보호하지 않으므로 Relaxed 로 충분하다.
+            self.contended.fetch_add(1, Relaxed);
+        }
+        guard
+    }
+
+    #[cfg(feature = "contention-stats")]
+    pub fn contention_count(&self) -> u64 {
+        self.contended.load(Relaxed)
+    }
+
+    // poisoned 는 잠금을 쥔 채로만 바뀌므로, 잠근 뒤에 Relaxed 로 읽어도 된다.
+    pub fn is_poisoned(&self) -> bool {
+        self.poisoned.load(Relaxed)
+    }
+
+    pub fn into_inner(self) -> LockResult<T> {
+        let poisoned = self.is_poisoned();
+        let value = self.value.into_inner();
+        if poisoned {
+            Err(PoisonError { guard: value })
+        } else {
+            Ok(value)
+        }
+    }
+
+    pub fn get_mut(&mut self) -> LockResult<&mut T> {
+        let poisoned = self.is_poisoned();
+        let value = self.value.get_mut();
+        if poisoned {
+            Err(PoisonError { guard: value })
+        } else {
+            Ok(value)
+        }
+    }
+
+    fn guard(&self) -> LockResult<MutexGuard<'_, T>> {
+        let guard = MutexGuard { mutex: self, panicking: thread::panicking() };
+        if self.is_poisoned() {
+            Err(PoisonError { guard })
+        } else {
+            Ok(guard)
+        }
+    }
+}
+
+fn lock_contended(state: &AtomicU32) {
+    let mut spin_count = 0;
+
+    while state.load(Relaxed) == 1 && spin_count < 100 {
+        spin_count += 1;
+        std::hint::spin_loop();
+    }
+
+    if state.compare_exchange(0, 1, Acquire, Relaxed).is_ok() {
+        return;
+    }
+
+    while state.swap(2, Acquire) != 0 {
+        wait(state, 2);
+    }
+}
+
+impl<T> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.value.get() }
+    }
+}
+
+impl<T> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.value.get() }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for MutexGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl<T> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        if !self.panicking && thread::panicking() {
+            self.mutex.poisoned.store(true, Relaxed);
+        }
+        // 대기하는 스레드가 있을 때만 wake 시스템 콜을 호출한다.
+        if self.mutex.state.swap(0, Release) == 2 {
+            wake_one(&self.mutex.state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use crate::ch9::mutex::Mutex;
+
+    #[test]
+    fn mutex_test() {
+        let m = Mutex::new(0u64);
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..100_000 {
+                        *m.lock().unwrap() += 1;
+                    }
+                });
+            }
+        });
+        assert_eq!(*m.lock().unwrap(), 400_000);
+    }
+
+    #[test]
+    fn try_lock_test() {
+        let m = Mutex::new(0);
+        let guard = m.lock().unwrap();
+        assert!(m.try_lock().is_none());
+        drop(guard);
+        assert!(m.try_lock().is_some());
+    }
+
+    #[cfg(feature = "contention-stats")]
+    #[test]
+    fn try_lock_counted_counts_failures() {
+        let m = Mutex::new(0);
+        let guard = m.lock().unwrap();
+        thread::scope(|s| {
+            s.spawn(|| {
+                for i in 1..=10 {
+                    assert!(m.try_lock_counted().is_none());
+                    assert_eq!(m.contention_count(), i);
+                }
+            });
+        });
+        drop(guard);
+        assert!(m.try_lock_counted().is_some());
+        assert_eq!(m.contention_count(), 10);
+    }
+
+    #[test]
+    fn panic_poisons() {
+        let mut m = Mutex::new(0);
+        let result = thread::scope(|s| {
+            s.spawn(|| {
+                let mut guard = m.lock().unwrap();
+                *guard = 1;
+                panic!("panic while holding the lock");
+            })
+            .join()
+        });
+        assert!(result.is_err());
+        assert!(m.is_poisoned());
+
+        // poison 되어도 값은 꺼낼 수 있다.
+        let guard = m.lock().unwrap_err().into_inner();
+        assert_eq!(*guard, 1);
+        drop(guard);
+        assert!(m.get_mut().is_err());
+        assert_eq!(m.into_inner().unwrap_err().into_inner(), 1);
+    }
+}
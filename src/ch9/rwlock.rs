@@ -0,0 +1,148 @@
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+use crate::ch9::futex::{wait, wake_all, wake_one};
+
+pub struct RwLock<T> {
+    // 짝수: 읽기 잠금의 수 * 2
+    // 1: 쓰기 잠김
+    state: AtomicU32,
+    // 쓰기 잠금을 기다리는 writer 의 수.
+    // 0 이 아니면 새로운 reader 는 들어오지 않고 기다려서 writer 가 굶지 않게 한다.
+    writers_waiting: AtomicU32,
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T> Sync for RwLock<T> where T: Send + Sync {}
+
+pub struct ReadGuard<'a, T> {
+    rwlock: &'a RwLock<T>,
+}
+
+pub struct WriteGuard<'a, T> {
+    rwlock: &'a RwLock<T>,
+}
+
+impl<T> RwLock<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            state: AtomicU32::new(0),
+            writers_waiting: AtomicU32::new(0),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    pub fn read(&self) -> ReadGuard<'_, T> {
+        loop {
+            let s = self.state.load(Relaxed);
+            if s & 1 == 1 {
+                wait(&self.state, s);
+                continue;
+            }
+
+            let w = self.writers_waiting.load(Relaxed);
+            if w != 0 {
+                wait(&self.writers_waiting, w);
+                continue;
+            }
+
+            assert!(s != u32::MAX - 1, "too many readers");
+            if self.state.compare_exchange_weak(s, s + 2, Acquire, Relaxed).is_ok() {
+                return ReadGuard { rwlock: self };
+            }
+        }
+    }
+
+    pub fn write(&self) -> WriteGuard<'_, T> {
+        self.writers_waiting.fetch_add(1, Relaxed);
+        loop {
+            let s = self.state.load(Relaxed);
+            if s == 0 {
+                if self.state.compare_exchange(0, 1, Acquire, Relaxed).is_ok() {
+                    break;
+                }
+                continue;
+            }
+            wait(&self.state, s);
+        }
+        self.writers_waiting.fetch_sub(1, Relaxed);
+        wake_all(&self.writers_waiting);
+        WriteGuard { rwlock: self }
+    }
+}
+
+impl<T> Deref for ReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.rwlock.value.get() }
+    }
+}
+
+impl<T> Deref for WriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.rwlock.value.get() }
+    }
+}
+
+impl<T> DerefMut for WriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.rwlock.value.get() }
+    }
+}
+
+impl<T> Drop for ReadGuard<'_, T> {
+    fn drop(&mut self) {
+        // 마지막 reader 가 나가면 기다리는 writer 하나를 깨운다.
+        if self.rwlock.state.fetch_sub(2, Release) == 2 {
+            wake_one(&self.rwlock.state);
+        }
+    }
+}
+
+impl<T> Drop for WriteGuard<'_, T> {
+    fn drop(&mut self) {
+        self.rwlock.state.store(0, Release);
+        wake_all(&self.rwlock.state);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use crate::ch9::rwlock::RwLock;
+
+    #[test]
+    fn rwlock_test() {
+        let lock = RwLock::new((0u64, 0u64));
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    for _ in 0..10_000 {
+                        let guard = lock.read();
+                        assert_eq!(guard.0, guard.1);
+                    }
+                });
+            }
+            s.spawn(|| {
+                for i in 1..=1000 {
+                    let mut guard = lock.write();
+                    guard.0 = i;
+                    guard.1 = i;
+                }
+            });
+        });
+        assert_eq!(*lock.read(), (1000, 1000));
+    }
+
+    #[test]
+    fn concurrent_readers() {
+        let lock = RwLock::new(1);
+        let a = lock.read();
+        let b = lock.read();
+        assert_eq!(*a + *b, 2);
+    }
+}
@@ -0,0 +1,86 @@
+use std::sync::atomic::{AtomicI64, AtomicU32};
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+use crate::ch9::futex::{wait, wake_one};
+
+pub struct Semaphore {
+    // 남은 permit 수. 음수이면 그 절댓값만큼의 스레드가 기다리고 있다.
+    permits: AtomicI64,
+    // release 가 기다리는 스레드에게 넘겨준 permit 수. futex 로 기다릴 값이다.
+    wakeups: AtomicU32,
+}
+
+impl Semaphore {
+    pub const fn new(permits: i64) -> Self {
+        assert!(permits >= 0, "permits must not be negative");
+        Self {
+            permits: AtomicI64::new(permits),
+            wakeups: AtomicU32::new(0),
+        }
+    }
+
+    pub fn acquire(&self) {
+        if self.permits.fetch_sub(1, Acquire) > 0 {
+            return;
+        }
+        // permit 이 없으므로 release 가 wakeups 로 하나를 넘겨줄 때까지 기다린다.
+        loop {
+            let w = self.wakeups.load(Relaxed);
+            if w == 0 {
+                wait(&self.wakeups, 0);
+            } else if self.wakeups.compare_exchange(w, w - 1, Acquire, Relaxed).is_ok() {
+                return;
+            }
+        }
+    }
+
+    pub fn try_acquire(&self) -> bool {
+        self.permits
+            .fetch_update(Acquire, Relaxed, |p| (p > 0).then_some(p - 1))
+            .is_ok()
+    }
+
+    pub fn release(&self) {
+        if self.permits.fetch_add(1, Release) < 0 {
+            // 기다리는 스레드가 있으므로 permit 을 그 스레드에게 직접 넘겨준다.
+            self.wakeups.fetch_add(1, Release);
+            wake_one(&self.wakeups);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use std::time::Duration;
+    use crate::ch9::semaphore::Semaphore;
+
+    #[test]
+    fn at_most_two_inside() {
+        let semaphore = Semaphore::new(2);
+        let in_flight = AtomicUsize::new(0);
+        let max_in_flight = AtomicUsize::new(0);
+
+        thread::scope(|s| {
+            for _ in 0..5 {
+                s.spawn(|| {
+                    for _ in 0..20 {
+                        semaphore.acquire();
+                        let now = in_flight.fetch_add(1, Relaxed) + 1;
+                        max_in_flight.fetch_max(now, Relaxed);
+                        thread::sleep(Duration::from_micros(100));
+                        in_flight.fetch_sub(1, Relaxed);
+                        semaphore.release();
+                    }
+                });
+            }
+        });
+
+        assert_eq!(in_flight.load(Relaxed), 0);
+        assert!(max_in_flight.load(Relaxed) <= 2);
+        assert!(semaphore.try_acquire());
+        assert!(semaphore.try_acquire());
+        assert!(!semaphore.try_acquire());
+    }
+}
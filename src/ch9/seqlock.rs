@@ -0,0 +1,99 @@
+use std::sync::atomic::{fence, AtomicU32, AtomicU64};
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+
+// reader 는 잠금을 잡지 않고, 읽는 동안 seq 가 바뀌었으면 다시 읽는다.
+// reader 와 writer 가 data 에 동시에 접근하므로 data 는 atomic 이어야 한다.
+// (volatile 은 data race 를 막아 주지 않는다. 일반 메모리를 동시에 읽고 쓰면 값을 버리더라도 UB 다.)
+// 그래서 값을 N 개의 u64 로 나눠서 Relaxed 로 읽고 쓰고, 찢어진 값은 seq 비교로 버린다.
+// 순서는 word 들이 아니라 seq 와 fence 가 맞춘다.
+//
+// 임의의 T 를 받지 않는 것은 padding 때문이다. padding 은 초기화되지 않은 byte 라서 u64 로 옮길 수 없다.
+// 구조체는 ch9::config 처럼 필드를 word 로 바꿔서 넣는다.
+pub struct SeqLock<const N: usize> {
+    // 짝수: 쓰는 중이 아님
+    // 홀수: writer 가 쓰는 중
+    seq: AtomicU32,
+    data: [AtomicU64; N],
+}
+
+impl<const N: usize> SeqLock<N> {
+    pub const fn new(value: [u64; N]) -> Self {
+        let mut data = [const { AtomicU64::new(0) }; N];
+        let mut i = 0;
+        while i < N {
+            data[i] = AtomicU64::new(value[i]);
+            i += 1;
+        }
+        Self { seq: AtomicU32::new(0), data }
+    }
+
+    pub fn read(&self) -> [u64; N] {
+        loop {
+            let s1 = self.seq.load(Acquire);
+            if s1 & 1 == 1 {
+                std::hint::spin_loop();
+                continue;
+            }
+            let value = std::array::from_fn(|i| self.data[i].load(Relaxed));
+            // data 를 읽은 것이 아래의 seq load 보다 먼저 일어나게 한다.
+            fence(Acquire);
+            if self.seq.load(Relaxed) == s1 {
+                return value;
+            }
+        }
+    }
+
+    pub fn write(&self, value: [u64; N]) {
+        let mut s = self.seq.load(Relaxed);
+        loop {
+            if s & 1 == 1 {
+                std::hint::spin_loop();
+                s = self.seq.load(Relaxed);
+                continue;
+            }
+            match self.seq.compare_exchange_weak(s, s + 1, Acquire, Relaxed) {
+                Ok(_) => break,
+                Err(e) => s = e,
+            }
+        }
+        // 홀수로 바꾼 것이 data 를 쓰는 것보다 먼저 보이게 한다.
+        fence(Release);
+        for (word, v) in self.data.iter().zip(value) {
+            word.store(v, Relaxed);
+        }
+        self.seq.store(s.wrapping_add(2), Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use crate::ch9::seqlock::SeqLock;
+
+    // `cargo +nightly miri test ch9::seqlock` 로 돌리면 data race 가 없는지 Miri 가 확인한다.
+    #[test]
+    fn seqlock_test() {
+        let writes = if cfg!(miri) { 200 } else { 100_000 };
+        let lock = SeqLock::new([0u64; 4]);
+        let done = AtomicBool::new(false);
+        thread::scope(|s| {
+            for _ in 0..4 {
+                s.spawn(|| {
+                    while !done.load(Relaxed) {
+                        let value = lock.read();
+                        assert!(value.iter().all(|&v| v == value[0]), "torn read: {value:?}");
+                    }
+                });
+            }
+            s.spawn(|| {
+                for i in 1..=writes {
+                    lock.write([i; 4]);
+                }
+                done.store(true, Relaxed);
+            });
+        });
+        assert_eq!(lock.read(), [writes; 4]);
+    }
+}
@@ -0,0 +1,115 @@
+use std::sync::Mutex;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::{Acquire, Relaxed, Release};
+use std::thread;
+use std::thread::Thread;
+
+// Go 의 sync.WaitGroup 처럼, 남은 작업 수가 0 이 될 때까지 기다린다.
+// CountDownLatch 와 달리 진행 중에도 add 로 작업을 늘릴 수 있다.
+// 단, add 는 그 작업을 기다릴 wait 보다 먼저 일어나야 한다. 보통은 작업을 시작하는 쪽이 작업을 띄우기 전에 add 한다.
+// 0 이 될 때마다 epoch 를 올리므로, wait 는 잠깐 0 이 되었다가 add 로 다시 늘어난 경우도 놓치지 않고 돌아온다.
+pub struct WaitGroup {
+    count: AtomicUsize,
+    epoch: AtomicUsize,
+    waiting: Mutex<Vec<Thread>>,
+}
+
+impl WaitGroup {
+    pub const fn new() -> Self {
+        Self {
+            count: AtomicUsize::new(0),
+            epoch: AtomicUsize::new(0),
+            waiting: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn add(&self, n: usize) {
+        self.count.fetch_add(n, Relaxed);
+    }
+
+    // Release: 작업에서 한 일이 wait 에서 돌아온 스레드에게 보이게 한다.
+    pub fn done(&self) {
+        let previous = self
+            .count
+            .fetch_update(Release, Relaxed, |count| count.checked_sub(1))
+            .expect("done called more times than add");
+        if previous == 1 {
+            self.epoch.fetch_add(1, Release);
+            for t in self.waiting.lock().unwrap().drain(..) {
+                t.unpark();
+            }
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.count.load(Relaxed)
+    }
+
+    pub fn wait(&self) {
+        // count 를 보기 전에 epoch 를 읽어야, 그 사이에 0 이 되었다가 다시 늘어나도 epoch 가 바뀐 것을 본다.
+        let epoch = self.epoch.load(Acquire);
+        if self.count.load(Acquire) == 0 {
+            return;
+        }
+        // 다시 확인하기 전에 먼저 등록해야 마지막 done 의 unpark 를 놓치지 않는다.
+        self.waiting.lock().unwrap().push(thread::current());
+        while self.count.load(Acquire) != 0 && self.epoch.load(Acquire) == epoch {
+            thread::park();
+        }
+    }
+}
+
+impl Default for WaitGroup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+    use std::time::Duration;
+    use crate::ch9::waitgroup::WaitGroup;
+
+    #[test]
+    fn waits_for_dynamically_added_work() {
+        let group = WaitGroup::new();
+        let finished = AtomicUsize::new(0);
+        thread::scope(|s| {
+            for i in 0..5 {
+                group.add(1);
+                let (group, finished) = (&group, &finished);
+                s.spawn(move || {
+                    // 일부 작업은 끝나기 전에 하위 작업을 더 띄운다.
+                    for _ in 0..i {
+                        group.add(1);
+                        s.spawn(move || {
+                            thread::sleep(Duration::from_millis(10));
+                            finished.fetch_add(1, Relaxed);
+                            group.done();
+                        });
+                    }
+                    finished.fetch_add(1, Relaxed);
+                    group.done();
+                });
+            }
+            group.wait();
+            // 5개의 작업과 0 + 1 + 2 + 3 + 4 개의 하위 작업
+            assert_eq!(finished.load(Relaxed), 15);
+            assert_eq!(group.count(), 0);
+        });
+    }
+
+    #[test]
+    fn wait_with_nothing_added() {
+        WaitGroup::new().wait();
+    }
+
+    #[test]
+    #[should_panic(expected = "done called more times than add")]
+    fn too_many_done() {
+        WaitGroup::new().done();
+    }
+}
@@ -0,0 +1,9 @@
+pub mod ch1;
+pub mod ch2;
+pub mod ch3;
+pub mod ch4;
+pub mod ch5;
+pub mod ch6;
+pub mod ch7;
+pub mod ch9;
+pub mod ch10;
@@ -0,0 +1,56 @@
+// loom 으로 unsafe_channel 들의 가능한 모든 실행 순서를 검사한다.
+// `RUSTFLAGS="--cfg loom" cargo test --release --test loom` 로 실행한다.
+// 다른 테스트는 loom 의 타입을 loom::model 밖에서 쓰게 되므로 이 target 만 돌린다.
+#![cfg(loom)]
+
+mod unsafe_channel {
+    use loom::sync::Arc;
+    use loom::thread;
+    use rust_playground::ch5::channel::unsafe_channel::Channel;
+
+    #[test]
+    fn send_receive() {
+        loom::model(|| {
+            let channel = Arc::new(Channel::new());
+            let sender = channel.clone();
+            let t = thread::spawn(move || sender.send(String::from("hello world!")));
+            while !channel.is_ready() {
+                thread::yield_now();
+            }
+            assert_eq!(channel.receive(), "hello world!");
+            t.join().unwrap();
+        });
+    }
+}
+
+mod unsafe_channel_ordered {
+    use std::sync::atomic::Ordering::{self, Acquire, Relaxed, Release};
+    use loom::sync::Arc;
+    use loom::thread;
+    use rust_playground::ch5::channel::unsafe_channel_ordered::Channel;
+
+    fn handshake(store: Ordering, load: Ordering) {
+        loom::model(move || {
+            let channel = Arc::new(Channel::new_with_ordering(store, load));
+            let sender = channel.clone();
+            let t = thread::spawn(move || sender.send(String::from("hello world!")).unwrap());
+            while !channel.is_ready() {
+                thread::yield_now();
+            }
+            assert_eq!(channel.try_receive().as_deref(), Ok("hello world!"));
+            t.join().unwrap();
+        });
+    }
+
+    #[test]
+    fn release_acquire_is_safe() {
+        handshake(Release, Acquire);
+    }
+
+    // Relaxed 이면 메시지를 쓰는 것과 읽는 것 사이에 happens-before 가 없어서 loom 이 data race 로 잡는다.
+    #[test]
+    #[should_panic(expected = "Causality violation")]
+    fn relaxed_is_a_data_race() {
+        handshake(Relaxed, Relaxed);
+    }
+}